@@ -1,16 +1,45 @@
 defmodule OpenCL.Array do
   use OpenCL.NativeStruct
+  import Kernel, except: [div: 2, min: 2, max: 2]
   alias OpenCL.Array
 
   defdelegate new(number_type, numbers), to: Native, as: :array_new
 
   defdelegate filled_with(number_type, number, count), to: Native, as: :array_new_filled_with
 
+  @doc """
+  Builds an array from a binary of raw little-endian numbers of `number_type`.
+
+  The binary's size must be a multiple of the size of `number_type`.
+  """
+  defdelegate from_binary(number_type, binary), to: Native, as: :array_from_binary
+
+  @doc "Returns the contents of the array as a binary of raw little-endian numbers."
+  defdelegate to_binary(array), to: Native, as: :array_to_binary
+
+  @doc """
+  Returns the contents of the array as a binary without copying them.
+
+  The binary shares the array's memory, so the array is marked as shared
+  and can no longer be modified: `push/2` and `extend/2` return an error
+  afterwards. The numbers are in the host's native byte order.
+  """
+  defdelegate to_shared_binary(array), to: Native, as: :array_to_shared_binary
+
+  @doc "Returns true once the array's memory has been shared via `to_shared_binary/1`."
+  defdelegate shared?(array), to: Native, as: :array_is_shared
+
   defdelegate to_list(array), to: Native, as: :array_data
 
   defdelegate length(array), to: Native, as: :array_length
 
-  defdelegate push(array, number), to: Native, as: :array_push
+  def push(array, number) do
+    case Native.array_push(array, number) do
+      {:ok, {}} -> :ok
+      {} -> :ok
+      err -> err
+    end
+  end
 
   def extend(array, %Array{} = other) do
     case Native.array_extend_from_array(array, other) do
@@ -28,7 +57,141 @@ defmodule OpenCL.Array do
     end
   end
 
+  @doc "Returns the number at `index`."
+  defdelegate get(array, index), to: Native, as: :array_get
+
+  @doc """
+  Overwrites the number at `index`. The number is cast to the array's type and
+  must fit it, e.g. `300` or `1.5` can't be set in a `:u8` array.
+  """
+  def set(array, index, number) do
+    case Native.array_set(array, index, number) do
+      {:ok, {}} -> :ok
+      {} -> :ok
+      err -> err
+    end
+  end
+
+  @doc "Returns a new flat array of the `len` numbers starting at `start`."
+  defdelegate slice(array, start, len), to: Native, as: :array_slice
+
+  @doc """
+  Overwrites the numbers starting at `offset` with the numbers of a list or
+  of another array of the same type.
+  """
+  def put_slice(array, offset, %Array{} = other) do
+    case Native.array_put_slice_from_array(array, offset, other) do
+      {:ok, {}} -> :ok
+      {} -> :ok
+      err -> err
+    end
+  end
+
+  def put_slice(array, offset, numbers) when is_list(numbers) do
+    case Native.array_put_slice_from_list(array, offset, numbers) do
+      {:ok, {}} -> :ok
+      {} -> :ok
+      err -> err
+    end
+  end
+
   defdelegate type(array), to: Native, as: :array_number_type
 
   defdelegate type_cast(array, number_type), to: Native, as: :array_cast
+
+  @doc """
+  Casts the array to `number_type`, handling numbers that don't fit by `mode`:
+
+    * `:wrapping` - like `type_cast/2`: integers wrap around and floats are
+      truncated, with NaN becoming 0.
+    * `:saturating` - numbers become the closest number of `number_type`. NaN
+      becomes 0 for integer types and stays NaN for floats.
+    * `:checked` - returns an error naming the index and value of the first
+      number that doesn't fit (complex numbers with an imaginary part don't
+      fit real types, and NaN doesn't fit integer types). NaN and infinities
+      cast to floats as themselves.
+  """
+  defdelegate type_cast(array, number_type, mode), to: Native, as: :array_cast_with_mode
+
+  @doc """
+  Returns the shape of the array as a tuple, e.g. `{2, 3}`.
+
+  Arrays without a shape are flat: `{length}`. Pushing to or extending an
+  array flattens it.
+  """
+  defdelegate dims(array), to: Native, as: :array_dims
+
+  @doc "Returns the row-major strides (in elements) of each axis of the array."
+  defdelegate strides(array), to: Native, as: :array_strides
+
+  @doc """
+  Returns a copy of the array with the given shape.
+
+  The product of the shape must match the array's length. The numbers are
+  copied, so modifying either array doesn't affect the other.
+  """
+  defdelegate reshape(array, shape), to: Native, as: :array_reshape
+
+  @doc """
+  Returns a row-major copy of the array with its axes permuted.
+
+  `axes` is a tuple such as `{1, 0}` giving the new order of the axes; by
+  default the order of the axes is reversed.
+  """
+  def transpose(array, axes \\ nil), do: Native.array_transpose(array, axes)
+
+  @doc "Returns a copy of the indices `start..(stop - 1)` along `axis`."
+  defdelegate slice_axis(array, axis, start, stop), to: Native, as: :array_slice_axis
+
+  @elementwise_ops [
+    add: "Adds",
+    sub: "Subtracts",
+    mul: "Multiplies",
+    div: "Divides",
+    min: "Takes the minimum of",
+    max: "Takes the maximum of"
+  ]
+
+  for {op, verb} <- @elementwise_ops do
+    @doc """
+    #{verb} each number of `array` and the matching number of `other`.
+
+    `other` is either an array of the same type and length or a scalar,
+    which is cast to the array's type and must fit in it, e.g. `300` is an
+    error for a `:u8` array and `1.5` for an `:i32` array. Integers wrap on
+    overflow and integer division by zero is an error. The result has the
+    shape of `array`.
+    """
+    def unquote(op)(%Array{} = array, %Array{} = other) do
+      Native.array_elementwise(array, unquote(op), other)
+    end
+
+    def unquote(op)(%Array{} = array, scalar) do
+      Native.array_elementwise_scalar(array, unquote(op), scalar)
+    end
+  end
+
+  @reductions [
+    sum: "the sum of the numbers",
+    product: "the product of the numbers",
+    min: "the smallest number",
+    max: "the largest number",
+    mean: "the mean of the numbers as a float",
+    argmin: "the index of the first smallest number",
+    argmax: "the index of the first largest number"
+  ]
+
+  for {op, description} <- @reductions do
+    @doc """
+    Returns #{description} in the array.
+
+    Integers are accumulated without overflowing 64 bits and floats are
+    accumulated as 64-bit floats. A NaN result is an error; see
+    `nan_#{op}/1` to leave NaNs out instead.
+    """
+    def unquote(op)(%Array{} = array), do: Native.array_reduce(array, unquote(op), false)
+
+    @doc "Like `#{op}/1`, but NaNs are left out."
+    def unquote(:"nan_#{op}")(%Array{} = array), do: Native.array_reduce(array, unquote(op), true)
+  end
 end
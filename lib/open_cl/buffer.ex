@@ -11,6 +11,21 @@ defmodule OpenCL.Buffer do
   method(:length)
   method(:number_type)
 
+  @doc """
+  Creates a buffer over the `len` numbers of `buffer` starting at number `offset`.
+
+  The sub-buffer shares its memory with `buffer` (no copy is made) and keeps
+  `buffer` alive. Most devices require the byte offset of a sub-buffer to be a
+  multiple of the device's base address alignment (often 128 bytes).
+  """
+  @spec sub_buffer(t(), non_neg_integer(), non_neg_integer()) :: {:ok, t()} | {:error, any()}
+  def sub_buffer(%__MODULE__{} = buffer, offset, len) do
+    Native.buffer_self_sub_buffer(buffer, offset, len)
+  end
+
+  @spec sub_buffer?(t()) :: boolean()
+  def sub_buffer?(%__MODULE__{} = buffer), do: Native.buffer_self_is_sub_buffer(buffer)
+
   # defdelegate to_array(buf), to: Native, as: :buffer_to_array
   # defdelegate reference_count(buf), to: Native, as: :buffer_reference_count
 end
@@ -0,0 +1,59 @@
+defmodule OpenCL.BufferMap do
+  @moduledoc """
+  A region of a buffer mapped into host memory by `OpenCL.Session.map_buffer/5`.
+
+  The region is unmapped by `unmap/1` or, failing that, when the map is
+  garbage collected. Garbage collection only enqueues the unmap without
+  waiting for it, so call `unmap/1` before relying on the device seeing the
+  writes, e.g. before enqueuing a kernel that reads the buffer.
+  """
+  use OpenCL.NativeStruct
+  alias OpenCL.Array
+  alias OpenCL.BufferMap
+
+  method(:length)
+  method(:number_type)
+
+  @spec mapped?(BufferMap.t()) :: boolean()
+  def mapped?(%BufferMap{} = buffer_map), do: Native.buffer_map_self_is_mapped(buffer_map)
+
+  @doc """
+  Copies the mapped numbers into a new array.
+  """
+  @spec to_array(BufferMap.t()) :: {:ok, Array.t()} | {:error, any()}
+  def to_array(%BufferMap{} = buffer_map), do: Native.buffer_map_self_to_array(buffer_map)
+
+  @doc """
+  Returns a binary that points directly at the mapped memory without copying.
+
+  The map can no longer be written to or unmapped afterwards; it is unmapped
+  once both the map and the binary have been garbage collected.
+  """
+  @spec to_shared_binary(BufferMap.t()) :: {:ok, binary()} | {:error, any()}
+  def to_shared_binary(%BufferMap{} = buffer_map) do
+    Native.buffer_map_self_to_shared_binary(buffer_map)
+  end
+
+  @doc """
+  Overwrites the mapped numbers starting at `offset` with `array`.
+  """
+  @spec write_array(BufferMap.t(), non_neg_integer(), Array.t()) :: :ok | {:error, any()}
+  def write_array(%BufferMap{} = buffer_map, offset, %Array{} = array) do
+    case Native.buffer_map_self_write_array(buffer_map, offset, array) do
+      {:ok, {}} -> :ok
+      {:error, _} = err -> err
+    end
+  end
+
+  @doc """
+  Unmaps the region, making any writes visible to the device. Blocks until
+  the unmap is done.
+  """
+  @spec unmap(BufferMap.t()) :: :ok | {:error, any()}
+  def unmap(%BufferMap{} = buffer_map) do
+    case Native.buffer_map_self_unmap(buffer_map) do
+      {:ok, {}} -> :ok
+      {:error, _} = err -> err
+    end
+  end
+end
@@ -0,0 +1,50 @@
+defmodule OpenCL.BuildError do
+  alias OpenCL.BuildError
+
+  @type severity :: :error | :warning | :note
+
+  @type diagnostic :: %{
+    line: non_neg_integer(),
+    column: non_neg_integer(),
+    severity: severity(),
+    message: String.t()
+  }
+
+  @type device_log :: %{
+    device_name: String.t(),
+    log: String.t(),
+    diagnostics: [diagnostic()]
+  }
+
+  @type t :: %BuildError{
+    status_code: integer(),
+    options: String.t(),
+    logs: [device_log()]
+  }
+
+  defexception [status_code: -11, options: "", logs: []]
+
+  @spec message(t()) :: String.t()
+  def message(%BuildError{status_code: status_code, logs: logs}) do
+    details =
+      logs
+      |> Enum.flat_map(fn %{device_name: device_name, diagnostics: diagnostics} ->
+        Enum.map(diagnostics, fn %{line: line, column: column, severity: severity, message: message} ->
+          "  #{device_name}:#{line}:#{column}: #{severity}: #{message}"
+        end)
+      end)
+      |> Enum.join("\n")
+
+    "OpenCL program build failed with status code #{status_code}\n" <> details
+  end
+
+  @doc """
+  Returns every diagnostic of every device log with the given `severity`.
+  """
+  @spec diagnostics(t(), severity()) :: [diagnostic()]
+  def diagnostics(%BuildError{logs: logs}, severity \\ :error) do
+    logs
+    |> Enum.flat_map(fn %{diagnostics: diagnostics} -> diagnostics end)
+    |> Enum.filter(fn %{severity: s} -> s == severity end)
+  end
+end
@@ -0,0 +1,83 @@
+defmodule OpenCL.BuildOptions do
+  alias OpenCL.BuildOptions
+
+  @type define_value :: String.t() | number() | atom() | nil
+
+  @type t :: %BuildOptions{
+          defines: %{optional(String.t() | atom()) => define_value()},
+          include_paths: [String.t()],
+          cl_std: String.t() | nil,
+          fast_relaxed_math: boolean(),
+          extra: [String.t()]
+        }
+
+  @type native :: %{
+          defines: [{String.t(), String.t() | nil}],
+          include_paths: [String.t()],
+          cl_std: String.t() | nil,
+          fast_relaxed_math: boolean(),
+          extra: [String.t()]
+        }
+
+  defstruct defines: %{},
+            include_paths: [],
+            cl_std: nil,
+            fast_relaxed_math: false,
+            extra: []
+
+  @doc """
+  Builds the compiler options for a program.
+
+  ## Options
+
+    * `:defines` - a map of preprocessor defines, e.g. `%{"TILE" => 16}`.
+      A `nil` value defines the name without a value. Values are quoted as
+      needed, so they reach the compiler unchanged; a NUL byte in any option
+      is an error.
+    * `:include_paths` - directories passed as `-I` flags.
+    * `:cl_std` - the OpenCL C version, e.g. `"CL1.2"`.
+    * `:fast_relaxed_math` - passes `-cl-fast-relaxed-math` when `true`.
+    * `:extra` - any other flags, passed through as-is.
+  """
+  def build(%BuildOptions{} = opts), do: opts
+
+  def build(opts) do
+    %BuildOptions{
+      defines: Keyword.get(opts, :defines, %{}),
+      include_paths: Keyword.get(opts, :include_paths, []),
+      cl_std: Keyword.get(opts, :cl_std),
+      fast_relaxed_math: Keyword.get(opts, :fast_relaxed_math, false),
+      extra: Keyword.get(opts, :extra, [])
+    }
+  end
+
+  def default, do: %BuildOptions{}
+
+  @spec to_native(t()) :: native()
+  def to_native(%BuildOptions{} = opts) do
+    %{
+      defines: Enum.map(opts.defines, fn {name, value} -> {to_string(name), define_value(value)} end),
+      include_paths: opts.include_paths,
+      cl_std: opts.cl_std,
+      fast_relaxed_math: opts.fast_relaxed_math,
+      extra: opts.extra
+    }
+  end
+
+  defp define_value(nil), do: nil
+  defp define_value(value), do: to_string(value)
+
+  def errors(%BuildOptions{} = opts) do
+    validate(opts.defines, :defines, &is_map/1) ++
+      validate(opts.include_paths, :include_paths, &string_list?/1) ++
+      validate(opts.cl_std, :cl_std, &(is_nil(&1) or is_binary(&1))) ++
+      validate(opts.fast_relaxed_math, :fast_relaxed_math, &is_boolean/1) ++
+      validate(opts.extra, :extra, &string_list?/1)
+  end
+
+  defp validate(value, field, valid?) do
+    if valid?.(value), do: [], else: [{field, "is invalid"}]
+  end
+
+  defp string_list?(value), do: is_list(value) and Enum.all?(value, &is_binary/1)
+end
@@ -0,0 +1,39 @@
+defmodule OpenCL.Event do
+  use OpenCL.NativeStruct
+  alias OpenCL.Event
+  alias OpenCL.ProfilingInfo
+
+  @spec wait(Event.t()) :: :ok | {:error, any()}
+  def wait(%Event{} = event) do
+    case Native.event_self_wait(event) do
+      {:ok, {}} -> :ok
+      {:error, _} = err -> err
+    end
+  end
+
+  @spec status(Event.t()) :: {:ok, Native.event_status()} | {:error, any()}
+  def status(%Event{} = event), do: Native.event_self_status(event)
+
+  @doc """
+  Returns the device timestamps of the event's command.
+
+  The session must have been created with the `:profiling_enabled` command
+  queue property and the command must have completed.
+  """
+  @spec profiling_info(Event.t()) :: {:ok, ProfilingInfo.t()} | {:error, any()}
+  def profiling_info(%Event{} = event), do: Native.event_self_profiling_info(event)
+
+  @doc """
+  Sends `message` to `pid` once the event's command has finished.
+
+  The message is sent whether or not the command succeeded. Use `status/1`
+  to find out how it went.
+  """
+  @spec on_complete(Event.t(), pid(), any()) :: :ok | {:error, any()}
+  def on_complete(%Event{} = event, pid \\ self(), message) do
+    case Native.event_self_on_complete(event, pid, message) do
+      {:ok, {}} -> :ok
+      {:error, _} = err -> err
+    end
+  end
+end
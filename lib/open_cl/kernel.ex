@@ -0,0 +1,40 @@
+defmodule OpenCL.Kernel do
+  use OpenCL.NativeStruct
+  alias OpenCL.Kernel
+  alias OpenCL.KernelOp
+
+  @type arg_info :: %{
+          name: String.t(),
+          type_name: String.t(),
+          address_qualifier: :global | :local | :constant | :private
+        }
+
+  method(:name)
+  method(:num_args)
+  method(:arg_info)
+
+  @doc """
+  Binds `arg` to the kernel at `index`. The arg stays bound for every
+  following launch until it is set again.
+  """
+  @spec set_arg(t(), non_neg_integer(), KernelOp.arg()) :: :ok | {:error, any()}
+  def set_arg(%Kernel{} = kernel, index, arg) when is_integer(index) and index >= 0 do
+    case Native.kernel_self_set_arg(kernel, index, arg) do
+      {:ok, {}} -> :ok
+      :invalid_variant -> {:error, [arg: "invalid arg at index #{index}"]}
+      {:error, _} = err -> err
+    end
+  end
+
+  @spec set_args(t(), KernelOp.args()) :: :ok | {:error, any()}
+  def set_args(%Kernel{} = kernel, args) when is_list(args) do
+    args
+    |> Enum.with_index()
+    |> Enum.reduce_while(:ok, fn {arg, index}, :ok ->
+      case set_arg(kernel, index, arg) do
+        :ok -> {:cont, :ok}
+        err -> {:halt, err}
+      end
+    end)
+  end
+end
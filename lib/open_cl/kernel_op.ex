@@ -1,10 +1,36 @@
 defmodule OpenCL.KernelOp do
   alias OpenCL.Buffer
   alias OpenCL.KernelOp
+  alias OpenCL.Native
   alias OpenCL.CommandQueueOpts
   alias OpenCL.Work
 
-  @type arg :: number() | Buffer.t()
+  @typedoc """
+  A kernel arg. Untagged scalars are cast to the type the kernel declares
+  for the arg; scalars can also be tagged with a type, e.g. `{:i32, 10}`.
+  Executing errors if a scalar does not fit its type, e.g. `1.5` for an
+  `int` or `300` for a `uchar`.
+
+  `{:local, type, count}` allocates `__local` scratch memory for `count`
+  numbers of `type` per work group.
+
+  Vector values are passed as `{vector_type, components}`, e.g.
+  `{:float4, {1.0, 2.0, 3.0, 4.0}}`. Like scalars, each component must fit
+  the vector's element type.
+
+  Complex scalars are passed as `{re, im}` (a `:c32`, i.e. an OpenCL
+  `float2`) or tagged, e.g. `{:c64, {1.0, -1.0}}`.
+  """
+  @type arg ::
+          number()
+          | Native.complex()
+          | {Native.number_type(), number() | Native.complex()}
+          | {:local, Native.number_type(), pos_integer()}
+          | {vector_type(), tuple()}
+          | Buffer.t()
+
+  @typedoc "An OpenCL C vector type such as `:float4` or `:uchar16`."
+  @type vector_type :: atom()
   @type args :: [arg]
   @type arg_index :: non_neg_integer()
   @type name :: String.t()
@@ -27,6 +53,28 @@ defmodule OpenCL.KernelOp do
             command_queue_opts: nil,
             args: []
 
+  @number_types [
+    :u8,
+    :i8,
+    :u16,
+    :i16,
+    :u32,
+    :i32,
+    :f32,
+    :u64,
+    :i64,
+    :f64,
+    :f16,
+    :c32,
+    :c64
+  ]
+
+  @complex_types [:c32, :c64]
+
+  @vector_types (for scalar <- ~w(char uchar short ushort int uint long ulong float double),
+                     width <- [2, 3, 4, 8, 16],
+                     do: :"#{scalar}#{width}")
+
   @spec build(name(), work_builder(), args, options) :: t()
   def build(name, work_builder, args, opts \\ []) when is_list(args) do
     %KernelOp{
@@ -117,9 +165,22 @@ defmodule OpenCL.KernelOp do
 
   @doc false
   def is_arg?(n) when is_number(n), do: true
+  def is_arg?({re, im}) when is_number(re) and is_number(im), do: true
+  def is_arg?({t, n}) when t in @number_types and is_number(n), do: true
+  def is_arg?({t, {re, im}}) when t in @complex_types and is_number(re) and is_number(im), do: true
+  def is_arg?({t, components}) when t in @vector_types and is_tuple(components),
+    do: tuple_size(components) == vector_width(t) and Enum.all?(Tuple.to_list(components), &is_number/1)
+  def is_arg?({:local, t, count}) when t in @number_types and is_integer(count) and count > 0, do: true
   def is_arg?(%Buffer{}), do: true
   def is_arg?(_), do: false
 
+  defp vector_width(vector_type) do
+    vector_type
+    |> Atom.to_string()
+    |> String.replace(~r/^[a-z]+/, "")
+    |> String.to_integer()
+  end
+
   defp returning_errors(%KernelOp{returning: ret, args: args}) do
     case ret do
       nil -> []
@@ -8,6 +8,14 @@ defmodule OpenCL.Native do
   alias OpenCL.MemConfig
   alias OpenCL.KernelOp
   alias OpenCL.CommandQueueProps
+  alias OpenCL.Event
+  alias OpenCL.Program
+  alias OpenCL.Kernel
+  alias OpenCL.Work
+  alias OpenCL.BuildOptions
+  alias OpenCL.ProfilingInfo
+  alias OpenCL.Dims
+  alias OpenCL.BufferMap
 
   import OpenCL.NifNotLoadedError, only: [err: 0]
 
@@ -17,7 +25,11 @@ defmodule OpenCL.Native do
 
   @type side_effect_output :: :ok | {:error, String.t()} | :invalid_variant
 
-  @type number_type :: :u8 | :i8 | :u16 | :i16 | :u32 | :i32 | :f32 | :u64 | :i64 | :f64
+  @type number_type ::
+          :u8 | :i8 | :u16 | :i16 | :u32 | :i32 | :f32 | :u64 | :i64 | :f64 | :f16 | :c32 | :c64
+
+  @typedoc "A complex number as `{re, im}`; used by the `:c32` and `:c64` number types."
+  @type complex :: {number(), number()}
 
   # PLATFORM
   @spec platform_default :: Platform.t()
@@ -289,12 +301,22 @@ defmodule OpenCL.Native do
   #   # @spec command_queue_self_reference_count(CommandQueue.t()) :: output(non_neg_integer())
   #   # def command_queue_self_reference_count(_command_queue), do: err()
 
-  @spec session_create_with_devices([Device.t()], String.t(), CommandQueueProps.t()) ::
-          output(Session.t())
-  def session_create_with_devices(_devices, _src, _props), do: err()
-
-  @spec session_create(String.t(), CommandQueueProps.t()) :: output(Session.t())
-  def session_create(_src, _props), do: err()
+  @spec session_create_with_devices(
+          [Device.t()],
+          String.t(),
+          CommandQueueProps.t(),
+          BuildOptions.native(),
+          String.t() | nil
+        ) :: output(Session.t())
+  def session_create_with_devices(_devices, _src, _props, _build_options, _cache_dir), do: err()
+
+  @spec session_create(
+          String.t(),
+          CommandQueueProps.t(),
+          BuildOptions.native(),
+          String.t() | nil
+        ) :: output(Session.t())
+  def session_create(_src, _props, _build_options, _cache_dir), do: err()
 
   @spec session_self_create_copy(Session.t()) :: output(Session.t())
   def session_self_create_copy(_session), do: err()
@@ -323,9 +345,138 @@ defmodule OpenCL.Native do
           output(Array.t())
   def session_self_read_buffer(_session, _buffer, _cq_opts), do: err()
 
+  @spec session_self_read_buffer_into(Session.t(), Buffer.t(), Array.t()) :: output({})
+  def session_self_read_buffer_into(_session, _buffer, _array), do: err()
+
+  @spec session_self_read_buffer_range(
+          Session.t(),
+          Buffer.t(),
+          non_neg_integer(),
+          non_neg_integer()
+        ) :: output(Array.t())
+  def session_self_read_buffer_range(_session, _buffer, _offset, _len), do: err()
+
+  @spec session_self_write_array_to_buffer_range(
+          Session.t(),
+          Buffer.t(),
+          non_neg_integer(),
+          Array.t()
+        ) :: output({})
+  def session_self_write_array_to_buffer_range(_session, _buffer, _offset, _array), do: err()
+
+  @typedoc "The `{row_pitch, slice_pitch}` of a buffer rect in elements; 0 means tightly packed."
+  @type pitches :: {non_neg_integer(), non_neg_integer()}
+
+  @spec session_self_read_buffer_rect(Session.t(), Buffer.t(), Dims.t(), Dims.t(), pitches()) ::
+          output(Array.t())
+  def session_self_read_buffer_rect(_session, _buffer, _origin, _region, _pitches), do: err()
+
+  @spec session_self_write_array_to_buffer_rect(
+          Session.t(),
+          Buffer.t(),
+          Dims.t(),
+          Dims.t(),
+          pitches(),
+          Array.t()
+        ) :: output({})
+  def session_self_write_array_to_buffer_rect(
+        _session,
+        _buffer,
+        _origin,
+        _region,
+        _pitches,
+        _array
+      ),
+      do: err()
+
+  @spec session_self_copy_buffer_rect(
+          Session.t(),
+          Buffer.t(),
+          Buffer.t(),
+          Dims.t(),
+          Dims.t(),
+          Dims.t(),
+          pitches(),
+          pitches()
+        ) :: output({})
+  def session_self_copy_buffer_rect(
+        _session,
+        _src,
+        _dst,
+        _src_origin,
+        _dst_origin,
+        _region,
+        _src_pitches,
+        _dst_pitches
+      ),
+      do: err()
+
+  @spec session_self_copy_buffer(
+          Session.t(),
+          Buffer.t(),
+          Buffer.t(),
+          non_neg_integer(),
+          non_neg_integer(),
+          non_neg_integer()
+        ) :: output({})
+  def session_self_copy_buffer(_session, _src, _dst, _src_offset, _dst_offset, _len), do: err()
+
+  @spec session_self_fill_buffer(
+          Session.t(),
+          Buffer.t(),
+          number() | complex(),
+          non_neg_integer(),
+          non_neg_integer()
+        ) :: output({})
+  def session_self_fill_buffer(_session, _buffer, _number, _offset, _len), do: err()
+
+  @type map_access :: :read | :write | :read_write
+
+  @spec session_self_map_buffer(
+          Session.t(),
+          Buffer.t(),
+          map_access(),
+          non_neg_integer(),
+          non_neg_integer()
+        ) :: output(BufferMap.t())
+  def session_self_map_buffer(_session, _buffer, _access, _offset, _len), do: err()
+
+  @spec session_self_write_array_to_buffer_profiled(
+          Session.t(),
+          Buffer.t(),
+          Array.t(),
+          CommandQueueOpts.native()
+        ) :: output(ProfilingInfo.t())
+  def session_self_write_array_to_buffer_profiled(_session, _buffer, _array, _cq_opts), do: err()
+
+  @spec session_self_read_buffer_profiled(Session.t(), Buffer.t(), CommandQueueOpts.native()) ::
+          output({Array.t(), ProfilingInfo.t()})
+  def session_self_read_buffer_profiled(_session, _buffer, _cq_opts), do: err()
+
   @spec session_self_execute_kernel_operation(Session.t(), KernelOp.t()) :: output({})
   def session_self_execute_kernel_operation(_session, _kernel_op), do: err()
 
+  @spec session_self_execute_kernel_operation_profiled(Session.t(), KernelOp.t()) ::
+          output(ProfilingInfo.t())
+  def session_self_execute_kernel_operation_profiled(_session, _kernel_op), do: err()
+
+  @spec session_self_execute_kernel_operation_async(Session.t(), KernelOp.t()) :: output(Event.t())
+  def session_self_execute_kernel_operation_async(_session, _kernel_op), do: err()
+
+  @spec session_self_execute_kernel_operation_notify(Session.t(), KernelOp.t(), pid(), reference()) ::
+          output({})
+  def session_self_execute_kernel_operation_notify(_session, _kernel_op, _pid, _ref), do: err()
+
+  @spec session_self_read_buffer_notify(Session.t(), Buffer.t(), pid(), reference()) :: output({})
+  def session_self_read_buffer_notify(_session, _buffer, _pid, _ref), do: err()
+
+  @spec session_self_launch_kernel(Session.t(), Kernel.t(), Work.native()) :: output({})
+  def session_self_launch_kernel(_session, _kernel, _work), do: err()
+
+  @spec session_self_launch_kernel_async(Session.t(), Kernel.t(), Work.native()) ::
+          output(Event.t())
+  def session_self_launch_kernel_async(_session, _kernel, _work), do: err()
+
   #   @spec session_self_device_name(Session.t()) :: output(String.t())
   #   def session_self_device_name(_session), do: err()
 
@@ -490,12 +641,24 @@ defmodule OpenCL.Native do
   #   @type(dims :: dim() | {dim()} | {dim(), dim()}, {dim(), dim(), dim()})
 
   # ARRAY
-  @spec array_new(number_type(), [number(), ...]) :: Array.t()
+  @spec array_new(number_type(), [number() | complex(), ...]) :: Array.t()
   def array_new(_number_type, _numbers), do: err()
 
-  @spec array_new_filled_with(number_type(), number(), non_neg_integer()) :: Array.t()
+  @spec array_new_filled_with(number_type(), number() | complex(), non_neg_integer()) :: Array.t()
   def array_new_filled_with(_number_type, _number, _count), do: err()
 
+  @spec array_from_binary(number_type(), binary()) :: output(Array.t())
+  def array_from_binary(_number_type, _binary), do: err()
+
+  @spec array_to_binary(Array.t()) :: binary()
+  def array_to_binary(_array), do: err()
+
+  @spec array_to_shared_binary(Array.t()) :: binary()
+  def array_to_shared_binary(_array), do: err()
+
+  @spec array_is_shared(Array.t()) :: boolean()
+  def array_is_shared(_array), do: err()
+
   def array_data(_array), do: err()
 
   def array_length(_array), do: err()
@@ -510,6 +673,60 @@ defmodule OpenCL.Native do
 
   def array_cast(_array, _number_type), do: err()
 
+  @type cast_mode :: :wrapping | :saturating | :checked
+
+  @spec array_cast_with_mode(Array.t(), number_type(), cast_mode()) :: output(Array.t())
+  def array_cast_with_mode(_array, _number_type, _mode), do: err()
+
+  @typedoc "The size of each axis of an array, e.g. `{rows, cols}`."
+  @type shape :: tuple()
+
+  @spec array_dims(Array.t()) :: shape()
+  def array_dims(_array), do: err()
+
+  @spec array_strides(Array.t()) :: shape()
+  def array_strides(_array), do: err()
+
+  @spec array_reshape(Array.t(), shape()) :: output(Array.t())
+  def array_reshape(_array, _shape), do: err()
+
+  @spec array_transpose(Array.t(), nil | tuple()) :: output(Array.t())
+  def array_transpose(_array, _axes), do: err()
+
+  @spec array_slice_axis(Array.t(), non_neg_integer(), non_neg_integer(), non_neg_integer()) ::
+          output(Array.t())
+  def array_slice_axis(_array, _axis, _start, _stop), do: err()
+
+  @type elementwise_op :: :add | :sub | :mul | :div | :min | :max
+
+  @spec array_elementwise(Array.t(), elementwise_op(), Array.t()) :: output(Array.t())
+  def array_elementwise(_left, _op, _right), do: err()
+
+  @spec array_elementwise_scalar(Array.t(), elementwise_op(), number() | complex()) ::
+          output(Array.t())
+  def array_elementwise_scalar(_array, _op, _scalar), do: err()
+
+  @type reduction_op :: :sum | :product | :min | :max | :mean | :argmin | :argmax
+
+  @spec array_reduce(Array.t(), reduction_op(), boolean()) :: output(number() | complex())
+  def array_reduce(_array, _op, _skip_nan), do: err()
+
+  @spec array_get(Array.t(), non_neg_integer()) :: output(number() | complex())
+  def array_get(_array, _index), do: err()
+
+  @spec array_set(Array.t(), non_neg_integer(), number() | complex()) :: output({})
+  def array_set(_array, _index, _number), do: err()
+
+  @spec array_slice(Array.t(), non_neg_integer(), non_neg_integer()) :: output(Array.t())
+  def array_slice(_array, _start, _len), do: err()
+
+  @spec array_put_slice_from_list(Array.t(), non_neg_integer(), [number() | complex()]) ::
+          output({})
+  def array_put_slice_from_list(_array, _offset, _list_of_numbers), do: err()
+
+  @spec array_put_slice_from_array(Array.t(), non_neg_integer(), Array.t()) :: output({})
+  def array_put_slice_from_array(_array, _offset, _other), do: err()
+
   #   @type buffer_access :: :read_only | :write_only | :read_write
 
   #   def buffer_build_from_array(_session, _dims, _number_type, _array, _access), do: err()
@@ -518,6 +735,74 @@ defmodule OpenCL.Native do
 
   def buffer_self_number_type(_buffer), do: err()
 
+  @spec buffer_self_sub_buffer(Buffer.t(), non_neg_integer(), non_neg_integer()) ::
+          output(Buffer.t())
+  def buffer_self_sub_buffer(_buffer, _offset, _len), do: err()
+
+  @spec buffer_self_is_sub_buffer(Buffer.t()) :: boolean()
+  def buffer_self_is_sub_buffer(_buffer), do: err()
+
   #   def buffer_reference_count(_buffer), do: err()
 
+  # BUFFER MAP
+  @spec buffer_map_self_to_array(BufferMap.t()) :: output(Array.t())
+  def buffer_map_self_to_array(_buffer_map), do: err()
+
+  @spec buffer_map_self_to_shared_binary(BufferMap.t()) :: output(binary())
+  def buffer_map_self_to_shared_binary(_buffer_map), do: err()
+
+  @spec buffer_map_self_write_array(BufferMap.t(), non_neg_integer(), Array.t()) :: output({})
+  def buffer_map_self_write_array(_buffer_map, _offset, _array), do: err()
+
+  @spec buffer_map_self_unmap(BufferMap.t()) :: output({})
+  def buffer_map_self_unmap(_buffer_map), do: err()
+
+  @spec buffer_map_self_is_mapped(BufferMap.t()) :: boolean()
+  def buffer_map_self_is_mapped(_buffer_map), do: err()
+
+  @spec buffer_map_self_length(BufferMap.t()) :: non_neg_integer()
+  def buffer_map_self_length(_buffer_map), do: err()
+
+  @spec buffer_map_self_number_type(BufferMap.t()) :: number_type()
+  def buffer_map_self_number_type(_buffer_map), do: err()
+
+  # EVENT
+  @type event_status :: :queued | :submitted | :running | :complete
+
+  @spec event_self_wait(Event.t()) :: output({})
+  def event_self_wait(_event), do: err()
+
+  @spec event_self_status(Event.t()) :: output(event_status())
+  def event_self_status(_event), do: err()
+
+  @spec event_self_profiling_info(Event.t()) :: output(ProfilingInfo.t())
+  def event_self_profiling_info(_event), do: err()
+
+  @spec event_self_on_complete(Event.t(), pid(), any()) :: output({})
+  def event_self_on_complete(_event, _pid, _message), do: err()
+
+  # PROGRAM
+  @spec program_create(Session.t(), String.t(), BuildOptions.native(), String.t() | nil) ::
+          output(Program.t())
+  def program_create(_session, _src, _build_options, _cache_dir), do: err()
+
+  @spec program_self_kernel_names(Program.t()) :: output([String.t()])
+  def program_self_kernel_names(_program), do: err()
+
+  @spec program_self_create_kernel(Program.t(), String.t()) :: output(Kernel.t())
+  def program_self_create_kernel(_program, _name), do: err()
+
+  # KERNEL
+  @spec kernel_self_name(Kernel.t()) :: output(String.t())
+  def kernel_self_name(_kernel), do: err()
+
+  @spec kernel_self_num_args(Kernel.t()) :: output(non_neg_integer())
+  def kernel_self_num_args(_kernel), do: err()
+
+  @spec kernel_self_arg_info(Kernel.t()) :: output([Kernel.arg_info()])
+  def kernel_self_arg_info(_kernel), do: err()
+
+  @spec kernel_self_set_arg(Kernel.t(), non_neg_integer(), KernelOp.arg()) :: output({})
+  def kernel_self_set_arg(_kernel, _index, _arg), do: err()
+
 end
@@ -0,0 +1,30 @@
+defmodule OpenCL.ProfilingInfo do
+  alias OpenCL.ProfilingInfo
+
+  @typedoc """
+  Device timestamps of a command in nanoseconds.
+
+  Only available for commands run on a session created with the
+  `:profiling_enabled` command queue property.
+  """
+  @type t :: %ProfilingInfo{
+          queued: non_neg_integer(),
+          submitted: non_neg_integer(),
+          start: non_neg_integer(),
+          end: non_neg_integer()
+        }
+
+  defstruct queued: 0, submitted: 0, start: 0, end: 0
+
+  @doc """
+  The time in nanoseconds the command spent executing on the device.
+  """
+  @spec duration(t()) :: non_neg_integer()
+  def duration(%ProfilingInfo{start: start, end: stop}), do: stop - start
+
+  @doc """
+  The time in nanoseconds from the command being queued until it finished.
+  """
+  @spec total_duration(t()) :: non_neg_integer()
+  def total_duration(%ProfilingInfo{queued: queued, end: stop}), do: stop - queued
+end
@@ -0,0 +1,47 @@
+defmodule OpenCL.Program do
+  use OpenCL.NativeStruct
+  alias OpenCL.BuildOptions
+  alias OpenCL.Kernel
+  alias OpenCL.Program
+  alias OpenCL.Session
+
+  @type create_option ::
+          {:build_options, BuildOptions.t() | keyword()}
+          | {:cache_dir, Path.t() | nil}
+
+  @doc """
+  Compiles `src` once for the device of `session`.
+
+  Kernels created from the program can be launched with any session that
+  shares the context of `session`.
+
+  ## Options
+
+    * `:build_options` - compiler options, see `OpenCL.BuildOptions.build/1`.
+    * `:cache_dir` - a directory to store and load program binaries in.
+      Binaries are keyed by the source, build options, device name and driver
+      version; on a miss the source is compiled and the binary stored.
+      Defaults to `config :open_cl, :program_cache_dir` (no caching if unset).
+  """
+  @spec create(Session.t(), String.t(), [create_option]) :: {:ok, t()} | {:error, any()}
+  def create(%Session{} = session, src, opts \\ []) when is_binary(src) do
+    build_options =
+      opts
+      |> Keyword.get(:build_options, BuildOptions.default())
+      |> BuildOptions.build()
+
+    cache_dir = Keyword.get(opts, :cache_dir, Application.get_env(:open_cl, :program_cache_dir))
+
+    case Native.program_create(session, src, BuildOptions.to_native(build_options), cache_dir) do
+      :invalid_variant -> {:error, BuildOptions.errors(build_options)}
+      result -> result
+    end
+  end
+
+  method(:kernel_names)
+
+  @spec create_kernel(t(), String.t()) :: {:ok, Kernel.t()} | {:error, any()}
+  def create_kernel(%Program{} = program, name) when is_binary(name) do
+    Native.program_self_create_kernel(program, name)
+  end
+end
@@ -2,20 +2,40 @@ defmodule OpenCL.Session do
   use OpenCL.NativeStruct
   alias OpenCL.Array
   alias OpenCL.Buffer
+  alias OpenCL.BufferMap
+  alias OpenCL.BuildOptions
   alias OpenCL.Device
+  alias OpenCL.Event
+  alias OpenCL.Kernel
   alias OpenCL.MemConfig
+  alias OpenCL.ProfilingInfo
   alias OpenCL.CommandQueueOpts
   alias OpenCL.Session
   alias OpenCL.KernelOp
   alias OpenCL.Work
   alias OpenCL.CommandQueueProps
+  alias OpenCL.Dims
 
   @type create_option ::
           {:command_queue_properties, CommandQueueProps.t()}
           | {:devices, [Device.t()]}
+          | {:build_options, BuildOptions.t() | keyword()}
+          | {:cache_dir, Path.t() | nil}
 
   @type create_result :: {:ok, [t()]} | {:error, any()}
 
+  @doc """
+  Creates a session with the program `src` for each device.
+
+  ## Options
+
+    * `:command_queue_properties` - see `OpenCL.CommandQueueProps`.
+    * `:devices` - the devices to create sessions for.
+    * `:build_options` - compiler options, see `OpenCL.BuildOptions.build/1`.
+    * `:cache_dir` - a directory to store and load program binaries in,
+      as for `OpenCL.Program.create/3`. Defaults to
+      `config :open_cl, :program_cache_dir` (no caching if unset).
+  """
   @spec create(String.t(), [create_option]) :: create_result()
   def create(src, opts \\ []) do
     case Keyword.fetch(opts, :devices) do
@@ -23,15 +43,24 @@ defmodule OpenCL.Session do
         create_with_devices(src, devices, opts)
       :error ->
         src
-        |> Native.session_create(get_props(opts))
+        |> Native.session_create(
+          get_props(opts),
+          native_build_options(opts),
+          get_cache_dir(opts)
+        )
         |> handle_return(opts)
     end
   end
 
   @spec create_with_devices(String.t(), [Device.t], [create_option]) :: create_result()
   def create_with_devices(src, devices, opts) do
-     devices
-    |> Native.session_create_with_devices(src, get_props(opts))
+    devices
+    |> Native.session_create_with_devices(
+      src,
+      get_props(opts),
+      native_build_options(opts),
+      get_cache_dir(opts)
+    )
     |> handle_return(opts)
   end
 
@@ -40,13 +69,29 @@ defmodule OpenCL.Session do
   defp handle_return({:error, _} = err, _), do: err
 
   defp create_session_errors(opts) do
-    CommandQueueProps.errors(get_props(opts))
+    CommandQueueProps.errors(get_props(opts)) ++ BuildOptions.errors(get_build_options(opts))
   end
 
   defp get_props(opts) do
     Keyword.get(opts, :command_queue_properties, CommandQueueProps.default())
   end
 
+  defp get_build_options(opts) do
+    opts
+    |> Keyword.get(:build_options, BuildOptions.default())
+    |> BuildOptions.build()
+  end
+
+  defp native_build_options(opts) do
+    opts
+    |> get_build_options()
+    |> BuildOptions.to_native()
+  end
+
+  defp get_cache_dir(opts) do
+    Keyword.get(opts, :cache_dir, Application.get_env(:open_cl, :program_cache_dir))
+  end
+
   method(:device)
   method(:create_copy)
 
@@ -74,6 +119,211 @@ defmodule OpenCL.Session do
     end
   end
 
+  @doc """
+  Reads all of `buffer` into the existing `array` instead of allocating a new one.
+
+  The array must have the buffer's number type. It is resized (dropping its
+  shape) if its length differs from the buffer's, so reading into the same
+  array in a loop only allocates once.
+  """
+  @spec read_buffer_into(Session.t(), Buffer.t(), Array.t()) :: :ok | {:error, any()}
+  def read_buffer_into(%Session{} = session, %Buffer{} = buffer, %Array{} = array) do
+    case Native.session_self_read_buffer_into(session, buffer, array) do
+      {:ok, {}} -> :ok
+      {:error, _} = err -> err
+    end
+  end
+
+  @doc """
+  Reads only the `len` numbers of `buffer` starting at number `offset`.
+  """
+  @spec read_buffer_range(Session.t(), Buffer.t(), non_neg_integer(), non_neg_integer()) ::
+          {:ok, Array.t()} | {:error, any()}
+  def read_buffer_range(%Session{} = session, %Buffer{} = buffer, offset, len) do
+    Native.session_self_read_buffer_range(session, buffer, offset, len)
+  end
+
+  @doc """
+  Writes all of `array` into `buffer` starting at number `offset`, leaving the
+  rest of the buffer untouched.
+  """
+  @spec write_buffer_range(Session.t(), Buffer.t(), non_neg_integer(), Array.t()) ::
+          :ok | {:error, any()}
+  def write_buffer_range(%Session{} = session, %Buffer{} = buffer, offset, %Array{} = array) do
+    case Native.session_self_write_array_to_buffer_range(session, buffer, offset, array) do
+      {:ok, {}} -> :ok
+      {:error, _} = err -> err
+    end
+  end
+
+  @type rect_option :: {:row_pitch, non_neg_integer()} | {:slice_pitch, non_neg_integer()}
+
+  @doc """
+  Reads the rectangular `region` of `buffer` starting at `origin` into a flat,
+  row-major array.
+
+  `origin` and `region` are `{x, y, z}` dims where `x` is in elements, `y` in
+  rows and `z` in slices. The `:row_pitch` and `:slice_pitch` options give the
+  layout of `buffer` in elements and default to 0, which means tightly packed
+  rows and slices of `region`.
+  """
+  @spec read_buffer_rect(Session.t(), Buffer.t(), Dims.t(), Dims.t(), [rect_option()]) ::
+          {:ok, Array.t()} | {:error, any()}
+  def read_buffer_rect(%Session{} = session, %Buffer{} = buffer, origin, region, opts \\ []) do
+    Native.session_self_read_buffer_rect(session, buffer, origin, region, pitches(opts))
+  end
+
+  @doc """
+  Writes the flat, row-major `array` into the rectangular `region` of `buffer`
+  starting at `origin`. See `read_buffer_rect/5` for the options.
+  """
+  @spec write_buffer_rect(Session.t(), Buffer.t(), Dims.t(), Dims.t(), Array.t(), [rect_option()]) ::
+          :ok | {:error, any()}
+  def write_buffer_rect(
+        %Session{} = session,
+        %Buffer{} = buffer,
+        origin,
+        region,
+        %Array{} = array,
+        opts \\ []
+      ) do
+    pitches = pitches(opts)
+
+    case Native.session_self_write_array_to_buffer_rect(
+           session,
+           buffer,
+           origin,
+           region,
+           pitches,
+           array
+         ) do
+      {:ok, {}} -> :ok
+      {:error, _} = err -> err
+    end
+  end
+
+  @doc """
+  Copies the rectangular `region` of `src` at `src_origin` to `dst` at
+  `dst_origin` without a round trip through the host.
+
+  Takes `:src_row_pitch`, `:src_slice_pitch`, `:dst_row_pitch` and
+  `:dst_slice_pitch` options; see `read_buffer_rect/5`.
+  """
+  @spec copy_buffer_rect(Session.t(), Buffer.t(), Buffer.t(), Dims.t(), Dims.t(), Dims.t(), keyword()) ::
+          :ok | {:error, any()}
+  def copy_buffer_rect(
+        %Session{} = session,
+        %Buffer{} = src,
+        %Buffer{} = dst,
+        src_origin,
+        dst_origin,
+        region,
+        opts \\ []
+      ) do
+    src_pitches = {Keyword.get(opts, :src_row_pitch, 0), Keyword.get(opts, :src_slice_pitch, 0)}
+    dst_pitches = {Keyword.get(opts, :dst_row_pitch, 0), Keyword.get(opts, :dst_slice_pitch, 0)}
+
+    case Native.session_self_copy_buffer_rect(
+           session,
+           src,
+           dst,
+           src_origin,
+           dst_origin,
+           region,
+           src_pitches,
+           dst_pitches
+         ) do
+      {:ok, {}} -> :ok
+      {:error, _} = err -> err
+    end
+  end
+
+  @doc """
+  Copies `len` numbers from `src` at `src_offset` to `dst` at `dst_offset`
+  without a round trip through the host. Both buffers must have the same
+  number type.
+  """
+  @spec copy_buffer(
+          Session.t(),
+          Buffer.t(),
+          Buffer.t(),
+          non_neg_integer(),
+          non_neg_integer(),
+          non_neg_integer()
+        ) :: :ok | {:error, any()}
+  def copy_buffer(%Session{} = session, %Buffer{} = src, %Buffer{} = dst, src_offset, dst_offset, len) do
+    case Native.session_self_copy_buffer(session, src, dst, src_offset, dst_offset, len) do
+      {:ok, {}} -> :ok
+      {:error, _} = err -> err
+    end
+  end
+
+  @doc """
+  Sets `len` numbers of `buffer` starting at `offset` to `number`.
+
+  Returns an error if `number` does not fit in the buffer's number type, e.g.
+  `-1` for a `:u8` buffer or `1.5` for an `:i32` buffer.
+  """
+  @spec fill_buffer(Session.t(), Buffer.t(), number() | Native.complex(), non_neg_integer(), non_neg_integer()) ::
+          :ok | {:error, any()}
+  def fill_buffer(%Session{} = session, %Buffer{} = buffer, number, offset, len) do
+    case Native.session_self_fill_buffer(session, buffer, number, offset, len) do
+      {:ok, {}} -> :ok
+      {:error, _} = err -> err
+    end
+  end
+
+  @doc """
+  Maps the `len` numbers of `buffer` starting at `offset` into host memory.
+
+  `access` is `:read`, `:write` or `:read_write`. On devices with host-unified
+  memory, or for buffers created with `mem_location: :keep_in_place`, this
+  avoids the copy made by `read_buffer/3`.
+  """
+  @spec map_buffer(Session.t(), Buffer.t(), Native.map_access(), non_neg_integer(), non_neg_integer()) ::
+          {:ok, BufferMap.t()} | {:error, any()}
+  def map_buffer(%Session{} = session, %Buffer{} = buffer, access, offset, len) do
+    Native.session_self_map_buffer(session, buffer, access, offset, len)
+  end
+
+  defp pitches(opts) do
+    {Keyword.get(opts, :row_pitch, 0), Keyword.get(opts, :slice_pitch, 0)}
+  end
+
+  @doc """
+  Like `write_buffer/4`, but also returns the device timestamps of the write.
+
+  The session must have been created with the `:profiling_enabled` command
+  queue property.
+  """
+  @spec write_buffer_profiled(Session.t(), Buffer.t(), Array.t(), keyword()) ::
+          {:ok, ProfilingInfo.t()} | {:error, any()}
+  def write_buffer_profiled(%Session{} = session, %Buffer{} = buffer, %Array{} = array, opts \\ []) do
+    cq_opts = native_cq_opts(opts)
+
+    case Native.session_self_write_array_to_buffer_profiled(session, buffer, array, cq_opts) do
+      {:ok, %ProfilingInfo{}} = ok -> ok
+      :invalid_variant -> {:error, write_buffer_errors(opts)}
+      {:error, _} = err -> err
+    end
+  end
+
+  @doc """
+  Like `read_buffer/3`, but also returns the device timestamps of the read.
+
+  The session must have been created with the `:profiling_enabled` command
+  queue property.
+  """
+  @spec read_buffer_profiled(Session.t(), Buffer.t(), keyword()) ::
+          {:ok, Array.t(), ProfilingInfo.t()} | {:error, any()}
+  def read_buffer_profiled(%Session{} = session, %Buffer{} = buffer, opts \\ []) do
+    case Native.session_self_read_buffer_profiled(session, buffer, native_cq_opts(opts)) do
+      {:ok, {%Array{} = array, %ProfilingInfo{} = info}} -> {:ok, array, info}
+      :invalid_variant -> {:error, read_buffer_errors(opts)}
+      {:error, _} = err -> err
+    end
+  end
+
   @spec execute_kernel(Session.t(), KernelOp.name(), KernelOp.work_builder(), KernelOp.args(), KernelOp.options()) :: :ok | {:error, binary | [any]} | {:ok, any}
   def execute_kernel(%Session{} = session, name, work_builder, args, opts \\ []) do
     work = Work.build(work_builder)
@@ -90,6 +340,117 @@ defmodule OpenCL.Session do
     end
   end
 
+  @doc """
+  Like `execute_kernel/5`, but also returns the device timestamps of the kernel.
+
+  Returns `{:ok, info}`, or `{:ok, returned, info}` when the `:returning` option is given.
+  The session must have been created with the `:profiling_enabled` command
+  queue property.
+  """
+  @spec execute_kernel_profiled(Session.t(), KernelOp.name(), KernelOp.work_builder(), KernelOp.args(), KernelOp.options()) :: {:ok, ProfilingInfo.t()} | {:ok, any, ProfilingInfo.t()} | {:error, binary | [any]}
+  def execute_kernel_profiled(%Session{} = session, name, work_builder, args, opts \\ []) do
+    work = Work.build(work_builder)
+
+    kernel_op =
+      name
+      |> KernelOp.build(work, args, opts)
+      |> KernelOp.to_native()
+
+    case Native.session_self_execute_kernel_operation_profiled(session, kernel_op) do
+      {:ok, %ProfilingInfo{} = info} ->
+        case returning(kernel_op) do
+          :ok -> {:ok, info}
+          {:ok, ret} -> {:ok, ret, info}
+        end
+      :invalid_variant -> {:error, execute_kernel_errors(kernel_op)}
+      {:error, _} = err -> err
+    end
+  end
+
+  @spec execute_kernel_async(Session.t(), KernelOp.name(), KernelOp.work_builder(), KernelOp.args(), KernelOp.options()) :: {:ok, Event.t()} | {:error, binary | [any]}
+  def execute_kernel_async(%Session{} = session, name, work_builder, args, opts \\ []) do
+    work = Work.build(work_builder)
+
+    kernel_op =
+      name
+      |> KernelOp.build(work, args, opts)
+      |> KernelOp.to_native()
+
+    case Native.session_self_execute_kernel_operation_async(session, kernel_op) do
+      {:ok, %Event{}} = okay_event -> okay_event
+      :invalid_variant -> {:error, execute_kernel_errors(kernel_op)}
+      {:error, _} = err -> err
+    end
+  end
+
+  @doc """
+  Enqueues the kernel and returns immediately with a reference.
+
+  When the kernel finishes `{:opencl_complete, ref, :ok | {:error, reason}}` is sent
+  to the `:pid` option (defaults to `self()`).
+  """
+  @spec execute_kernel_notify(Session.t(), KernelOp.name(), KernelOp.work_builder(), KernelOp.args(), KernelOp.options()) :: {:ok, reference()} | {:error, binary | [any]}
+  def execute_kernel_notify(%Session{} = session, name, work_builder, args, opts \\ []) do
+    work = Work.build(work_builder)
+    ref = Keyword.get_lazy(opts, :ref, &make_ref/0)
+    pid = Keyword.get(opts, :pid, self())
+
+    kernel_op =
+      name
+      |> KernelOp.build(work, args, opts)
+      |> KernelOp.to_native()
+
+    case Native.session_self_execute_kernel_operation_notify(session, kernel_op, pid, ref) do
+      {:ok, {}} -> {:ok, ref}
+      :invalid_variant -> {:error, execute_kernel_errors(kernel_op)}
+      {:error, _} = err -> err
+    end
+  end
+
+  @doc """
+  Starts reading the buffer and returns immediately with a reference.
+
+  When the read finishes `{:opencl_complete, ref, {:ok, array} | {:error, reason}}` is
+  sent to the `:pid` option (defaults to `self()`).
+  """
+  @spec read_buffer_notify(Session.t(), Buffer.t(), keyword()) :: {:ok, reference()} | {:error, any()}
+  def read_buffer_notify(%Session{} = session, %Buffer{} = buffer, opts \\ []) do
+    ref = Keyword.get_lazy(opts, :ref, &make_ref/0)
+    pid = Keyword.get(opts, :pid, self())
+
+    case Native.session_self_read_buffer_notify(session, buffer, pid, ref) do
+      {:ok, {}} -> {:ok, ref}
+      {:error, _} = err -> err
+    end
+  end
+
+  @doc """
+  Launches a kernel created with `OpenCL.Program.create_kernel/2` using its bound args
+  and waits for it to finish.
+  """
+  @spec launch_kernel(Session.t(), Kernel.t(), KernelOp.work_builder()) :: :ok | {:error, any()}
+  def launch_kernel(%Session{} = session, %Kernel{} = kernel, work_builder) do
+    work = work_builder |> Work.build() |> Work.to_native()
+
+    case Native.session_self_launch_kernel(session, kernel, work) do
+      {:ok, {}} -> :ok
+      :invalid_variant -> {:error, Work.errors(Work.build(work_builder))}
+      {:error, _} = err -> err
+    end
+  end
+
+  @spec launch_kernel_async(Session.t(), Kernel.t(), KernelOp.work_builder()) ::
+          {:ok, Event.t()} | {:error, any()}
+  def launch_kernel_async(%Session{} = session, %Kernel{} = kernel, work_builder) do
+    work = work_builder |> Work.build() |> Work.to_native()
+
+    case Native.session_self_launch_kernel_async(session, kernel, work) do
+      {:ok, %Event{}} = okay_event -> okay_event
+      :invalid_variant -> {:error, Work.errors(Work.build(work_builder))}
+      {:error, _} = err -> err
+    end
+  end
+
   defp returning(%KernelOp{} = kernel_op) do
     case KernelOp.get_return_value(kernel_op) do
       nil -> :ok
@@ -150,7 +511,10 @@ defmodule OpenCL.Session do
     :f32,
     :u64,
     :i64,
-    :f64
+    :f64,
+    :f16,
+    :c32,
+    :c64
   ]
 
   defp number_type_errors(t) when t in @number_types, do: []
@@ -5,4 +5,5 @@ rustler::atoms! {
     error,
     status_code_error,
     same_array,
+    opencl_complete,
 }
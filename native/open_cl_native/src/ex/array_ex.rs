@@ -1,22 +1,71 @@
 use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 use rustler::resource::ResourceArc;
-use rustler::types::atom::Atom;
-use rustler::{Encoder, NifStruct, Error, ListIterator, Decoder};
+use rustler::{Encoder, Env, NifStruct, Error, ListIterator, Decoder};
+use rustler::types::{Binary, OwnedBinary};
+use ndarray::{ArrayView, Axis, IxDyn, Slice};
 
+use crate::reduction;
 use crate::{
-    atoms, CastNumber, NumEx, NumberEx, NumberListEx, NumberType, NumberTyped, OutputEx,
-    RuntimeNumberList, 
+    BoundsError, CastMode, CastNumber, Dimension, ElementwiseOp, NumEx, ReductionOp, NumberEx, NumberListEx, NumberType, NumberTyped, OutputEx,
+    RuntimeNumberList, ShapeEx,
 };
 use crate::convert::{
     list_iterator_to_vec,
     list_iterator_to_rt_list,
+    le_bytes_to_rt_list,
+    rt_list_to_le_bytes,
 };
 
+#[derive(Debug, Fail, PartialEq, Eq, Clone)]
+pub enum ArrayError {
+    #[fail(display = "Array is shared with a binary and can no longer be modified")]
+    Shared,
+
+    #[fail(display = "Shape {:?} does not match the array length {}", _0, _1)]
+    ShapeMismatch(ShapeEx, usize),
+
+    #[fail(display = "Shape {:?} has more elements than fit in memory", _0)]
+    ShapeOverflow(ShapeEx),
+
+    #[fail(display = "Axes {:?} are not a permutation of {} axes", _0, _1)]
+    InvalidAxes(ShapeEx, usize),
+
+    #[fail(display = "Axis {} is out of range for {} axes", _0, _1)]
+    AxisOutOfRange(usize, usize),
+
+    #[fail(display = "Range {}..{} is out of bounds for an axis of length {}", _0, _1, _2)]
+    AxisRangeOutOfBounds(usize, usize, usize),
+
+    #[fail(display = "Array length mismatch - {} vs {}", _0, _1)]
+    LengthMismatch(usize, usize),
+
+    #[fail(display = "{:?} is not supported for {:?} arrays", _0, _1)]
+    UnsupportedOp(ElementwiseOp, NumberType),
+
+    #[fail(display = "Integer division by zero")]
+    DivisionByZero,
+
+    #[fail(display = "Reduction of an empty array")]
+    EmptyReduction,
+
+    #[fail(display = "Reduction overflowed")]
+    ReductionOverflow,
+
+    #[fail(display = "Reduction result is NaN or infinite")]
+    NonFiniteReduction,
+
+    #[fail(display = "Complex numbers have no ordering")]
+    UnorderedReduction,
+}
+
 #[derive(Debug)]
 pub struct Array {
     inner: RwLock<RuntimeNumberList>,
+    shared: AtomicBool,
+    shape: RwLock<Option<ShapeEx>>,
 }
 
 impl NumberTyped for Array {
@@ -32,9 +81,46 @@ impl Array {
     pub fn new(data: RuntimeNumberList) -> Array {
         Array {
             inner: RwLock::new(data),
+            shared: AtomicBool::new(false),
+            shape: RwLock::new(None),
         }
     }
 
+    /// Creates an array of the given shape. The shape must match the length of `data`.
+    pub fn with_shape(data: RuntimeNumberList, shape: ShapeEx) -> OutputEx<Array> {
+        Array::check_shape(&shape, data.len())?;
+        let array = Array::new(data);
+        array.set_shape(Some(shape));
+        Ok(array)
+    }
+
+    /// Checks that `shape` fits numbers of length `len`.
+    pub fn check_shape(shape: &ShapeEx, len: usize) -> OutputEx<()> {
+        if shape.checked_product().is_none() {
+            return Err(ArrayError::ShapeOverflow(shape.clone()).into());
+        }
+        if !shape.matches_length(len) {
+            return Err(ArrayError::ShapeMismatch(shape.clone(), len).into());
+        }
+        Ok(())
+    }
+
+    /// The shape of the array; arrays without a shape are flat, i.e. `{len}`.
+    pub fn shape(&self) -> ShapeEx {
+        let len = self.read_lock().len();
+        self.shape_with_len(len)
+    }
+
+    /// Like `shape`, for callers already holding a lock on numbers of length `len`.
+    pub fn shape_with_len(&self, len: usize) -> ShapeEx {
+        let shape = self.shape.read().unwrap().clone();
+        shape.unwrap_or_else(|| ShapeEx(vec![len]))
+    }
+
+    pub fn set_shape(&self, shape: Option<ShapeEx>) {
+        *self.shape.write().unwrap() = shape;
+    }
+
     pub fn rw_lock(&self) -> &RwLock<RuntimeNumberList> {
         &self.inner
     }
@@ -43,8 +129,31 @@ impl Array {
         self.inner.read().unwrap()
     }
 
-    pub fn write_lock(&self) -> RwLockWriteGuard<RuntimeNumberList> {
-        self.inner.write().unwrap()
+    /// Locks the numbers for writing.
+    ///
+    /// Fails once the numbers have been shared with a binary by `shared_bytes`.
+    pub fn write_lock(&self) -> OutputEx<RwLockWriteGuard<RuntimeNumberList>> {
+        let guard = self.inner.write().unwrap();
+        if self.is_shared() {
+            return Err(ArrayError::Shared.into());
+        }
+        Ok(guard)
+    }
+
+    pub fn is_shared(&self) -> bool {
+        self.shared.load(Ordering::SeqCst)
+    }
+
+    /// Returns the numbers' bytes for the lifetime of the `Array` itself.
+    ///
+    /// The array is marked as shared while the read lock is held, so no writer
+    /// can be in progress and every later `write_lock` fails. The allocation is
+    /// therefore never written to, moved or freed before the `Array` is dropped.
+    pub fn shared_bytes(&self) -> &[u8] {
+        let rt_list = self.read_lock();
+        self.shared.store(true, Ordering::SeqCst);
+        let bytes = rt_list.as_bytes();
+        unsafe { std::slice::from_raw_parts(bytes.as_ptr(), bytes.len()) }
     }
 
     pub fn into_inner(self) -> RuntimeNumberList {
@@ -59,6 +168,14 @@ pub struct ArrayEx {
     __native__: ResourceArc<Array>,
 }
 
+impl Clone for ArrayEx {
+    fn clone(&self) -> ArrayEx {
+        ArrayEx {
+            __native__: self.__native__.clone(),
+        }
+    }
+}
+
 impl fmt::Debug for ArrayEx {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "ArrayEx {{ native: {:?} }}", *self.read_lock())
@@ -81,14 +198,29 @@ impl ArrayEx {
         self.__native__.read_lock()
     }
 
-    pub fn write_lock(&self) -> RwLockWriteGuard<RuntimeNumberList> {
+    pub fn write_lock(&self) -> OutputEx<RwLockWriteGuard<RuntimeNumberList>> {
         self.__native__.write_lock()
     }
 
+    /// Returns the array's bytes as a binary that shares the array's allocation.
+    ///
+    /// The array can no longer be modified afterwards.
+    pub fn to_shared_binary<'a>(&self, env: Env<'a>) -> Binary<'a> {
+        self.__native__.make_binary(env, |array| array.shared_bytes())
+    }
+
     pub fn len(&self) -> usize {
         self.read_lock().len()
     }
 
+    pub fn shape(&self) -> ShapeEx {
+        self.__native__.shape()
+    }
+
+    pub fn set_shape(&self, shape: Option<ShapeEx>) {
+        self.__native__.set_shape(shape)
+    }
+
     pub fn is_empty(&self) -> bool {
         self.read_lock().len() == 0
     }
@@ -102,7 +234,11 @@ impl ArrayEx {
     // }
 
     pub fn is_same_array(&self, other: &ArrayEx) -> bool {
-        std::ptr::eq(self.__native__.rw_lock(), other.__native__.rw_lock())
+        std::ptr::eq(self.lock_ptr(), other.lock_ptr())
+    }
+
+    fn lock_ptr(&self) -> *const RwLock<RuntimeNumberList> {
+        self.__native__.rw_lock()
     }
 
     // pub fn into_rt_list(self) -> RuntimeNumberList {
@@ -116,6 +252,12 @@ impl From<RuntimeNumberList> for ArrayEx {
     }
 }
 
+impl ArrayEx {
+    pub fn with_shape(data: RuntimeNumberList, shape: ShapeEx) -> OutputEx<ArrayEx> {
+        Array::with_shape(data, shape).map(ArrayEx::from)
+    }
+}
+
 impl From<Array> for ArrayEx {
     fn from(arr: Array) -> ArrayEx {
         ArrayEx {
@@ -145,7 +287,9 @@ impl NumberTyped for ArrayEx {
 impl CastNumber for ArrayEx {
     fn cast_number(&self, number_type: NumberType) -> ArrayEx {
         let arr1 = self.read_lock();
-        ArrayEx::from((*arr1).cast_number(number_type))
+        let casted = ArrayEx::from((*arr1).cast_number(number_type));
+        casted.__native__.set_shape(self.__native__.shape.read().unwrap().clone());
+        casted
     }
 }
 
@@ -164,16 +308,42 @@ fn array_new<'a>(number_type: NumberType, iter: ListIterator<'a>) -> Result<Arra
     Ok(ArrayEx::from(rt_list))
 }
 
+#[rustler::nif(schedule = "DirtyCpu")]
+fn array_from_binary<'a>(number_type: NumberType, binary: Binary<'a>) -> OutputEx<ArrayEx> {
+    let rt_list = apply_number_type!(number_type, le_bytes_to_rt_list, [binary.as_slice()])?;
+    Ok(ArrayEx::from(rt_list))
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+fn array_to_binary<'a>(env: Env<'a>, array: ArrayEx) -> Binary<'a> {
+    let rt_list = array.read_lock();
+    let number_type = rt_list.number_type();
+    let mut owned = OwnedBinary::new(rt_list.len() * number_type.size_of()).unwrap();
+    apply_number_type!(number_type, rt_list_to_le_bytes, [&rt_list, owned.as_mut_slice()]);
+    owned.release(env)
+}
+
+#[rustler::nif]
+fn array_to_shared_binary<'a>(env: Env<'a>, array: ArrayEx) -> Binary<'a> {
+    array.to_shared_binary(env)
+}
+
+#[rustler::nif]
+fn array_is_shared(array: ArrayEx) -> bool {
+    array.__native__.is_shared()
+}
+
 fn _push_to_rt_list<T: NumberEx>(rt_list: &mut RuntimeNumberList, num: NumEx) {
     rt_list.push::<T>(From::from(num));
 }
 
 #[rustler::nif(schedule = "DirtyCpu")]
-fn array_push(array: ArrayEx, item: NumEx) -> Atom {
+fn array_push(array: ArrayEx, item: NumEx) -> OutputEx<()> {
     let number_type = array.number_type();
-    let mut rt_list = array.write_lock();
+    let mut rt_list = array.write_lock()?;
     apply_number_type!(number_type, _push_to_rt_list, [&mut rt_list, item]);
-    atoms::ok()
+    array.__native__.set_shape(None);
+    Ok(())
 }
 
 #[rustler::nif(schedule = "DirtyCpu")]
@@ -195,8 +365,10 @@ fn _extend_rt_with_list_iterator<'a, T: NumberEx + Decoder<'a>>(rt_list: &mut Ru
 #[rustler::nif(schedule = "DirtyCpu")]
 fn array_extend_from_list<'a>(array: ArrayEx, iter: ListIterator<'a>) -> Result<(), Error> {
     let number_type = array.number_type();
-    let mut rt_list = array.write_lock();
-    apply_number_type!(number_type, _extend_rt_with_list_iterator, [&mut rt_list, iter])
+    let mut rt_list = array.write_lock()?;
+    apply_number_type!(number_type, _extend_rt_with_list_iterator, [&mut rt_list, iter])?;
+    array.__native__.set_shape(None);
+    Ok(())
 }
 
 fn _extend_rt_list<T: NumberEx>(rt_list1: &mut RuntimeNumberList, rt_list2: &RuntimeNumberList) {
@@ -216,15 +388,15 @@ fn _extend_rt_list_with_slice<T: NumberEx>(rt_list: &mut RuntimeNumberList, othe
 fn array_extend_from_array(array: ArrayEx, other: ArrayEx) -> OutputEx<()> {
     let number_type = array.number_type();
     number_type.type_check(other.number_type())?;
-    let mut self_rt_list = array.write_lock();
+    let mut self_rt_list = array.write_lock()?;
     if array.is_same_array(&other) {
         apply_number_type!(number_type, _extend_same_rt_list, [&mut self_rt_list]);
-        Ok(())
     } else {
         let other_rt_list = other.read_lock();
         apply_number_type!(number_type, _extend_rt_list, [&mut self_rt_list, &other_rt_list]);
-        Ok(())
     }
+    array.__native__.set_shape(None);
+    Ok(())
     
     // match other_rt_list.number_type() {
     //     NumberType::U8 => self_rt_list.extend_from_slice::<u8>(other_rt_list.force_as_slice()),
@@ -282,3 +454,336 @@ fn array_number_type(array: ArrayEx) -> NumberType {
 fn array_cast(array: ArrayEx, number_type: NumberType) -> ArrayEx {
     array.cast_number(number_type)
 }
+
+#[rustler::nif(schedule = "DirtyCpu")]
+fn array_cast_with_mode(
+    array: ArrayEx,
+    number_type: NumberType,
+    mode: CastMode,
+) -> OutputEx<ArrayEx> {
+    let rt_list = array.read_lock();
+    let casted = rt_list.cast_with_mode(number_type, mode)?;
+    let shape = array.__native__.shape_with_len(rt_list.len());
+    ArrayEx::with_shape(casted, shape)
+}
+
+#[rustler::nif]
+fn array_dims(array: ArrayEx) -> ShapeEx {
+    array.shape()
+}
+
+#[rustler::nif]
+fn array_strides(array: ArrayEx) -> ShapeEx {
+    array.shape().row_major_strides()
+}
+
+/// Returns a copy of the array with the given shape.
+///
+/// The numbers are copied rather than shared so that the arrays can be modified
+/// independently; the shape is checked before copying.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn array_reshape(array: ArrayEx, shape: ShapeEx) -> OutputEx<ArrayEx> {
+    let rt_list = array.read_lock();
+    Array::check_shape(&shape, rt_list.len())?;
+    ArrayEx::with_shape(rt_list.clone(), shape)
+}
+
+/// A view of the numbers with `shape`, or a `ShapeMismatch` if they no longer fit it.
+fn shaped_view<'a, T: NumberEx>(
+    rt_list: &'a RuntimeNumberList,
+    shape: &[usize],
+) -> OutputEx<ArrayView<'a, T, IxDyn>> {
+    ArrayView::from_shape(shape, rt_list.force_as_slice::<T>())
+        .map_err(|_| ArrayError::ShapeMismatch(ShapeEx(shape.to_vec()), rt_list.len()).into())
+}
+
+fn _permute_axes<T: NumberEx>(
+    rt_list: &RuntimeNumberList,
+    shape: &[usize],
+    axes: &[usize],
+) -> OutputEx<RuntimeNumberList> {
+    let view = shaped_view::<T>(rt_list, shape)?;
+    let data: Vec<T> = view.permuted_axes(axes).iter().cloned().collect();
+    Ok(RuntimeNumberList::from_vec(data))
+}
+
+/// Returns a row-major copy of the array with its axes permuted by `axes`.
+///
+/// Without `axes` the order of the axes is reversed.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn array_transpose(array: ArrayEx, axes: Option<ShapeEx>) -> OutputEx<ArrayEx> {
+    let rt_list = array.read_lock();
+    let shape = array.__native__.shape_with_len(rt_list.len());
+    let ndim = shape.0.len();
+    let axes = axes.unwrap_or_else(|| ShapeEx((0..ndim).rev().collect()));
+    let mut sorted_axes = axes.0.clone();
+    sorted_axes.sort();
+    if sorted_axes != (0..ndim).collect::<Vec<usize>>() {
+        return Err(ArrayError::InvalidAxes(axes, ndim).into());
+    }
+    let new_shape = ShapeEx(axes.0.iter().map(|axis| shape.0[*axis]).collect());
+    let permuted = apply_number_type!(
+        rt_list.number_type(),
+        _permute_axes,
+        [&rt_list, shape.as_slice(), axes.as_slice()]
+    )?;
+    ArrayEx::with_shape(permuted, new_shape)
+}
+
+fn _slice_axis<T: NumberEx>(
+    rt_list: &RuntimeNumberList,
+    shape: &[usize],
+    axis: usize,
+    start: usize,
+    stop: usize,
+) -> OutputEx<RuntimeNumberList> {
+    let view = shaped_view::<T>(rt_list, shape)?;
+    let sliced = view.slice_axis(Axis(axis), Slice::from(start..stop));
+    Ok(RuntimeNumberList::from_vec(
+        sliced.iter().cloned().collect::<Vec<T>>(),
+    ))
+}
+
+/// Returns a row-major copy of the indices `start..stop` along `axis`.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn array_slice_axis(array: ArrayEx, axis: usize, start: usize, stop: usize) -> OutputEx<ArrayEx> {
+    let rt_list = array.read_lock();
+    let shape = array.__native__.shape_with_len(rt_list.len());
+    let ndim = shape.0.len();
+    if axis >= ndim {
+        return Err(ArrayError::AxisOutOfRange(axis, ndim).into());
+    }
+    let axis_len = shape.0[axis];
+    if start > stop || stop > axis_len {
+        return Err(ArrayError::AxisRangeOutOfBounds(start, stop, axis_len).into());
+    }
+    let mut new_shape = shape.clone();
+    new_shape.0[axis] = stop - start;
+    let sliced = apply_number_type!(
+        rt_list.number_type(),
+        _slice_axis,
+        [&rt_list, shape.as_slice(), axis, start, stop]
+    )?;
+    ArrayEx::with_shape(sliced, new_shape)
+}
+
+fn _elementwise<T: NumberEx>(
+    left: &RuntimeNumberList,
+    op: ElementwiseOp,
+    right: &[T],
+) -> OutputEx<RuntimeNumberList> {
+    let data: Option<Vec<T>> = left
+        .force_as_slice::<T>()
+        .iter()
+        .zip(right.iter().cycle())
+        .map(|(l, r)| l.elementwise(op, *r))
+        .collect();
+    match data {
+        Some(data) => Ok(RuntimeNumberList::from_vec(data)),
+        None => Err(ArrayError::DivisionByZero.into()),
+    }
+}
+
+fn _elementwise_arrays<T: NumberEx>(
+    left: &RuntimeNumberList,
+    op: ElementwiseOp,
+    right: &RuntimeNumberList,
+) -> OutputEx<RuntimeNumberList> {
+    _elementwise(left, op, right.force_as_slice::<T>())
+}
+
+fn _elementwise_scalar<T: NumberEx>(
+    left: &RuntimeNumberList,
+    op: ElementwiseOp,
+    scalar: NumEx,
+) -> OutputEx<RuntimeNumberList> {
+    let scalar: T = scalar.exact_cast_to()?;
+    _elementwise(left, op, &[scalar])
+}
+
+fn check_elementwise_op(op: ElementwiseOp, number_type: NumberType) -> OutputEx<()> {
+    if !op.supports(number_type) {
+        return Err(ArrayError::UnsupportedOp(op, number_type).into());
+    }
+    Ok(())
+}
+
+/// Applies `op` to each pair of numbers of two arrays of the same type and length.
+///
+/// The result has the shape of `left`.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn array_elementwise(left: ArrayEx, op: ElementwiseOp, right: ArrayEx) -> OutputEx<ArrayEx> {
+    let number_type = left.number_type();
+    number_type.type_check(right.number_type())?;
+    check_elementwise_op(op, number_type)?;
+    let (result, shape) = {
+        let left_rt_list = left.read_lock();
+        // reading the same array twice could deadlock on a waiting writer.
+        let right_guard = if left.is_same_array(&right) {
+            None
+        } else {
+            Some(right.read_lock())
+        };
+        let right_rt_list: &RuntimeNumberList = match &right_guard {
+            Some(guard) => guard,
+            None => &left_rt_list,
+        };
+        if left_rt_list.len() != right_rt_list.len() {
+            let e = ArrayError::LengthMismatch(left_rt_list.len(), right_rt_list.len());
+            return Err(e.into());
+        }
+        let result = apply_number_type!(
+            number_type,
+            _elementwise_arrays,
+            [&left_rt_list, op, right_rt_list]
+        )?;
+        (result, left.__native__.shape_with_len(left_rt_list.len()))
+    };
+    ArrayEx::with_shape(result, shape)
+}
+
+/// Applies `op` to each number of an array and a scalar cast to the array's type.
+///
+/// Errors if the array's type can't hold the scalar.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn array_elementwise_scalar(array: ArrayEx, op: ElementwiseOp, scalar: NumEx) -> OutputEx<ArrayEx> {
+    let number_type = array.number_type();
+    check_elementwise_op(op, number_type)?;
+    let (result, shape) = {
+        let rt_list = array.read_lock();
+        let result = apply_number_type!(number_type, _elementwise_scalar, [&rt_list, op, scalar])?;
+        (result, array.__native__.shape_with_len(rt_list.len()))
+    };
+    ArrayEx::with_shape(result, shape)
+}
+
+fn _reduce<T: NumberEx>(
+    rt_list: &RuntimeNumberList,
+    op: ReductionOp,
+    skip_nan: bool,
+) -> OutputEx<NumEx> {
+    let values = rt_list.force_as_slice::<T>().iter().map(|num| num.widen());
+    reduction::reduce(values, T::zero().widen(), op, skip_nan)
+}
+
+/// Reduces the numbers of an array with `op`. With `skip_nan` NaNs are ignored.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn array_reduce(array: ArrayEx, op: ReductionOp, skip_nan: bool) -> OutputEx<NumEx> {
+    let rt_list = array.read_lock();
+    apply_number_type!(rt_list.number_type(), _reduce, [&rt_list, op, skip_nan])
+}
+
+fn _get<T: NumberEx + Into<NumEx>>(rt_list: &RuntimeNumberList, index: usize) -> NumEx {
+    rt_list.force_as_slice::<T>()[index].into()
+}
+
+#[rustler::nif]
+fn array_get(array: ArrayEx, index: usize) -> OutputEx<NumEx> {
+    let rt_list = array.read_lock();
+    BoundsError::check_index(index, rt_list.len())?;
+    Ok(apply_number_type!(
+        rt_list.number_type(),
+        _get,
+        [&rt_list, index]
+    ))
+}
+
+fn _set<T: NumberEx>(rt_list: &mut RuntimeNumberList, index: usize, num: NumEx) -> OutputEx<()> {
+    rt_list.force_as_slice_mut::<T>()[index] = num.exact_cast_to()?;
+    Ok(())
+}
+
+/// Overwrites the number at `index` with `num`, which must fit in the array's type.
+#[rustler::nif]
+fn array_set(array: ArrayEx, index: usize, num: NumEx) -> OutputEx<()> {
+    let mut rt_list = array.write_lock()?;
+    BoundsError::check_index(index, rt_list.len())?;
+    let number_type = rt_list.number_type();
+    apply_number_type!(number_type, _set, [&mut rt_list, index, num])
+}
+
+fn _slice<T: NumberEx>(rt_list: &RuntimeNumberList, start: usize, len: usize) -> RuntimeNumberList {
+    RuntimeNumberList::from_vec(rt_list.force_as_slice::<T>()[start..start + len].to_vec())
+}
+
+/// Copies `len` numbers starting at `start` into a new flat array.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn array_slice(array: ArrayEx, start: usize, len: usize) -> OutputEx<ArrayEx> {
+    let rt_list = array.read_lock();
+    BoundsError::check_range(start, len, rt_list.len())?;
+    let sliced = apply_number_type!(rt_list.number_type(), _slice, [&rt_list, start, len]);
+    Ok(ArrayEx::from(sliced))
+}
+
+fn _put_slice<T: NumberEx>(
+    rt_list: &mut RuntimeNumberList,
+    offset: usize,
+    data: &[T],
+) -> OutputEx<()> {
+    BoundsError::check_range(offset, data.len(), rt_list.len())?;
+    rt_list.force_as_slice_mut::<T>()[offset..offset + data.len()].clone_from_slice(data);
+    Ok(())
+}
+
+fn _put_slice_from_list_iterator<'a, T: NumberEx + Decoder<'a>>(
+    rt_list: &mut RuntimeNumberList,
+    offset: usize,
+    iter: ListIterator<'a>,
+) -> Result<(), Error> {
+    let data = list_iterator_to_vec::<T>(iter)?;
+    Ok(_put_slice(rt_list, offset, &data[..])?)
+}
+
+/// Overwrites the numbers starting at `offset` with the numbers of a list.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn array_put_slice_from_list<'a>(
+    array: ArrayEx,
+    offset: usize,
+    iter: ListIterator<'a>,
+) -> Result<(), Error> {
+    let mut rt_list = array.write_lock()?;
+    let number_type = rt_list.number_type();
+    apply_number_type!(
+        number_type,
+        _put_slice_from_list_iterator,
+        [&mut rt_list, offset, iter]
+    )
+}
+
+fn _put_slice_from_rt_list<T: NumberEx>(
+    rt_list: &mut RuntimeNumberList,
+    offset: usize,
+    other: &RuntimeNumberList,
+) -> OutputEx<()> {
+    _put_slice(rt_list, offset, other.force_as_slice::<T>())
+}
+
+/// Overwrites the numbers starting at `offset` with the numbers of another array of the same type.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn array_put_slice_from_array(array: ArrayEx, offset: usize, other: ArrayEx) -> OutputEx<()> {
+    let number_type = array.number_type();
+    number_type.type_check(other.number_type())?;
+    if array.is_same_array(&other) {
+        // copy first; locking the same array for reading and writing would deadlock.
+        let other_rt_list = other.read_lock().clone();
+        let mut rt_list = array.write_lock()?;
+        return apply_number_type!(
+            number_type,
+            _put_slice_from_rt_list,
+            [&mut rt_list, offset, &other_rt_list]
+        );
+    }
+    // lock in address order so puts between two arrays in opposite directions can't deadlock.
+    let (mut rt_list, other_rt_list) = if array.lock_ptr() < other.lock_ptr() {
+        let rt_list = array.write_lock()?;
+        (rt_list, other.read_lock())
+    } else {
+        let other_rt_list = other.read_lock();
+        (array.write_lock()?, other_rt_list)
+    };
+    apply_number_type!(
+        number_type,
+        _put_slice_from_rt_list,
+        [&mut rt_list, offset, &other_rt_list]
+    )
+}
@@ -4,13 +4,16 @@ use std::fmt;
 use rustler::resource::ResourceArc;
 use rustler::{Encoder, NifMap, NifStruct, NifUnitEnum, NifUntaggedEnum};
 
-use opencl_core::ll::ClNumber;
+use opencl_core::ll::ffi::{self, cl_int, cl_mem};
+use opencl_core::ll::{ClNumber, MemPtr};
 use opencl_core::{Buffer, HostAccess, KernelAccess, MemConfig, MemLocation};
 
+use crate::ex::error_ex::check_status;
 use crate::{
     ArrayEx,
+    BoundsError,
     ErrorEx,
-    NumberEx,
+    MapAccessEx,
     // RuntimeNumberList,
     NumberListEx,
     NumberType,
@@ -26,6 +29,21 @@ pub enum BufferError {
         _0, _1
     )]
     TypeMismatch(NumberType, NumberType),
+
+    #[fail(display = "Sub-buffers can only be used through raw OpenCL calls")]
+    SubBuffer,
+
+    #[fail(display = "Buffer map is no longer mapped")]
+    NotMapped,
+
+    #[fail(display = "Buffer map is shared with a binary and can no longer be changed")]
+    MapShared,
+
+    #[fail(display = "Buffer map with {:?} access cannot be read", _0)]
+    MapNotReadable(MapAccessEx),
+
+    #[fail(display = "Buffer map with {:?} access cannot be written", _0)]
+    MapNotWritable(MapAccessEx),
 }
 
 /// A non-drop (because it lacks a T or a NumberType) pointer to a boxed buffer.
@@ -58,9 +76,49 @@ impl Clone for UntypedBuffer {
     }
 }
 
+/// A region of another buffer created by `clCreateSubBuffer`.
+///
+/// Holds the parent's resource so the parent outlives the sub-buffer. The
+/// sub-buffer's own `cl_mem` is released on drop.
+struct SubBuffer {
+    mem: cl_mem,
+    parent: BufferEx,
+    origin: usize,
+}
+
+unsafe impl Send for SubBuffer {}
+unsafe impl Sync for SubBuffer {}
+
+impl Clone for SubBuffer {
+    fn clone(&self) -> SubBuffer {
+        unsafe {
+            ffi::clRetainMemObject(self.mem);
+        }
+        SubBuffer {
+            mem: self.mem,
+            parent: self.parent.clone(),
+            origin: self.origin,
+        }
+    }
+}
+
+impl Drop for SubBuffer {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::clReleaseMemObject(self.mem);
+        }
+    }
+}
+
+#[derive(Clone)]
+enum BufferKind {
+    Whole(UntypedBuffer),
+    Sub(SubBuffer),
+}
+
 pub struct BufferWrapper {
     t: NumberType,
-    inner: UntypedBuffer,
+    inner: BufferKind,
 }
 
 impl Clone for BufferWrapper {
@@ -76,7 +134,7 @@ impl BufferWrapper {
     pub fn new<T: ClNumber + NumberTypedT>(buffer: Buffer<T>) -> BufferWrapper {
         BufferWrapper {
             t: T::number_type_of(),
-            inner: unsafe { UntypedBuffer::new(buffer) },
+            inner: BufferKind::Whole(unsafe { UntypedBuffer::new(buffer) }),
         }
     }
 
@@ -90,26 +148,100 @@ impl BufferWrapper {
         }
     }
 
+    fn untyped_buffer(&self) -> OutputEx<&UntypedBuffer> {
+        match &self.inner {
+            BufferKind::Whole(untyped) => Ok(untyped),
+            BufferKind::Sub(..) => Err(BufferError::SubBuffer.into()),
+        }
+    }
+
     pub fn into_buffer<T: ClNumber + NumberTypedT>(self) -> OutputEx<Buffer<T>> {
         self.type_check::<T>()?;
-        let native_buffer = unsafe { self.inner.into_buffer::<T>() };
+        let native_buffer = unsafe { self.untyped_buffer()?.into_buffer::<T>() };
         std::mem::forget(self);
         Ok(native_buffer)
     }
 
+    /// The whole buffer. Fails for sub-buffers, which only exist as raw `cl_mem`s.
     pub fn buffer<T: ClNumber + NumberTypedT>(&self) -> OutputEx<&Buffer<T>> {
         self.type_check::<T>()?;
-        Ok(unsafe { self.inner.get_ref() })
+        Ok(unsafe { self.untyped_buffer()?.get_ref() })
+    }
+
+    /// The raw `cl_mem` of the buffer. The pointer is only valid while this wrapper is alive.
+    pub fn mem_ptr(&self) -> cl_mem {
+        match &self.inner {
+            BufferKind::Whole(untyped) => unsafe {
+                let buf_ref: &Buffer<usize> = untyped.get_ref();
+                buf_ref.low_level_mem().mem_ptr()
+            },
+            BufferKind::Sub(sub) => sub.mem,
+        }
+    }
+
+    pub fn is_sub_buffer(&self) -> bool {
+        match self.inner {
+            BufferKind::Whole(..) => false,
+            BufferKind::Sub(..) => true,
+        }
     }
 
-    pub fn size<T: NumberEx>(&self) -> OutputEx<usize> {
-        let buf_ref: &Buffer<T> = unsafe { self.inner.get_ref() };
-        buf_ref.size().map_err(From::from)
+    /// The size of the buffer in bytes.
+    pub fn byte_size(&self) -> OutputEx<usize> {
+        let mut size: usize = 0;
+        let status = unsafe {
+            ffi::clGetMemObjectInfo(
+                self.mem_ptr(),
+                ffi::CL_MEM_SIZE,
+                std::mem::size_of::<usize>(),
+                &mut size as *mut usize as *mut libc::c_void,
+                std::ptr::null_mut(),
+            )
+        };
+        check_status(status)?;
+        Ok(size)
     }
 
-    pub fn len<T: NumberEx>(&self) -> OutputEx<usize> {
-        let size = self.size::<T>()?;
-        Ok(self.t.size_of() * size)
+    /// The number of numbers in the buffer.
+    pub fn len(&self) -> OutputEx<usize> {
+        Ok(self.byte_size()? / self.t.size_of())
+    }
+
+    /// Creates a sub-buffer of the `len` numbers starting at number `offset`.
+    ///
+    /// OpenCL doesn't allow sub-buffers of sub-buffers, so a sub-buffer of a
+    /// sub-buffer is created as a region of the root buffer instead.
+    pub fn sub_buffer(buffer: &BufferEx, offset: usize, len: usize) -> OutputEx<BufferWrapper> {
+        let wrapper = buffer.wrapper();
+        BoundsError::check_range(offset, len, wrapper.len()?)?;
+        let (root, origin) = match &wrapper.inner {
+            BufferKind::Whole(..) => (buffer.clone(), offset),
+            BufferKind::Sub(sub) => (sub.parent.clone(), sub.origin + offset),
+        };
+        let size_of_t = wrapper.t.size_of();
+        let region = ffi::cl_buffer_region {
+            origin: origin * size_of_t,
+            size: len * size_of_t,
+        };
+        let mut status: cl_int = 0;
+        let mem = unsafe {
+            ffi::clCreateSubBuffer(
+                root.wrapper().mem_ptr(),
+                0,
+                ffi::CL_BUFFER_CREATE_TYPE_REGION,
+                &region as *const ffi::cl_buffer_region as *const libc::c_void,
+                &mut status,
+            )
+        };
+        check_status(status)?;
+        Ok(BufferWrapper {
+            t: wrapper.t,
+            inner: BufferKind::Sub(SubBuffer {
+                mem,
+                parent: root,
+                origin,
+            }),
+        })
     }
 }
 
@@ -121,7 +253,9 @@ impl NumberTyped for BufferWrapper {
 
 impl Drop for BufferWrapper {
     fn drop(&mut self) {
-        let _ = unsafe { self.inner.into_buffer::<usize>() };
+        if let BufferKind::Whole(untyped) = &self.inner {
+            let _ = unsafe { untyped.into_buffer::<usize>() };
+        }
     }
 }
 
@@ -358,14 +492,19 @@ impl MemConfigEx {
     }
 }
 
-fn _buffer_len<T: NumberEx>(buff: &BufferWrapper) -> OutputEx<usize> {
-    buff.len::<T>()
-}
-
 #[rustler::nif(schedule = "DirtyCpu")]
 pub fn buffer_self_length(buffer: BufferEx) -> OutputEx<usize> {
-    let w = buffer.wrapper();
-    apply_number_type!(w.t, _buffer_len, [w])
+    buffer.wrapper().len()
+}
+
+#[rustler::nif]
+pub fn buffer_self_sub_buffer(buffer: BufferEx, offset: usize, len: usize) -> OutputEx<BufferEx> {
+    BufferWrapper::sub_buffer(&buffer, offset, len).map(BufferEx::from_buffer_wrapper)
+}
+
+#[rustler::nif]
+pub fn buffer_self_is_sub_buffer(buffer: BufferEx) -> bool {
+    buffer.wrapper().is_sub_buffer()
 }
 
 #[rustler::nif]
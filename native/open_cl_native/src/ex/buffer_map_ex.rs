@@ -0,0 +1,343 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use opencl_core::ll::ffi::{self, cl_command_queue, cl_event, cl_int, cl_map_flags};
+use rustler::resource::ResourceArc;
+use rustler::{Binary, Env, NifStruct, NifUnitEnum};
+
+use crate::ex::error_ex::check_status;
+use crate::{
+    ArrayEx, BoundsError, BufferError, BufferEx, Event, NumberEx, NumberType, NumberTyped,
+    OutputEx, RuntimeNumberList, SessionEx,
+};
+
+#[derive(NifUnitEnum, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum MapAccessEx {
+    Read,
+    Write,
+    ReadWrite,
+}
+
+impl MapAccessEx {
+    fn flags(self) -> cl_map_flags {
+        match self {
+            MapAccessEx::Read => ffi::CL_MAP_READ,
+            MapAccessEx::Write => ffi::CL_MAP_WRITE,
+            MapAccessEx::ReadWrite => ffi::CL_MAP_READ | ffi::CL_MAP_WRITE,
+        }
+    }
+
+    fn is_readable(self) -> bool {
+        self != MapAccessEx::Write
+    }
+
+    fn is_writable(self) -> bool {
+        self != MapAccessEx::Read
+    }
+}
+
+/// A host pointer to a region of a buffer mapped by `clEnqueueMapBuffer`.
+struct MappedPtr(*mut libc::c_void);
+
+/// A mapped region of a buffer.
+///
+/// Holds a retained command queue so the region can be unmapped, and the buffer
+/// so it outlives the mapping. The region is unmapped either explicitly by
+/// `unmap`, which blocks until the unmap is done, or on drop, which only enqueues
+/// the unmap and flushes the queue so the destructor never blocks.
+pub struct BufferMap {
+    queue: cl_command_queue,
+    buffer: BufferEx,
+    access: MapAccessEx,
+    len: usize,
+    mapped: RwLock<Option<MappedPtr>>,
+    shared: AtomicBool,
+}
+
+unsafe impl Send for BufferMap {}
+unsafe impl Sync for BufferMap {}
+
+impl BufferMap {
+    /// Maps the `len` numbers of `buffer` starting at number `offset`. Blocks until mapped.
+    pub fn map(
+        sess: &SessionEx,
+        buffer: BufferEx,
+        access: MapAccessEx,
+        offset: usize,
+        len: usize,
+    ) -> OutputEx<BufferMap> {
+        BoundsError::check_range(offset, len, buffer.wrapper().len()?)?;
+        let queue = sess.with_queue(|queue| queue);
+        check_status(unsafe { ffi::clRetainCommandQueue(queue) })?;
+        // from here on drop releases the queue.
+        let buffer_map = BufferMap {
+            queue,
+            buffer,
+            access,
+            len,
+            mapped: RwLock::new(None),
+            shared: AtomicBool::new(false),
+        };
+        let size_of_t = buffer_map.number_type().size_of();
+        let mut status: cl_int = 0;
+        let mut event_ptr: cl_event = std::ptr::null_mut();
+        let ptr = unsafe {
+            ffi::clEnqueueMapBuffer(
+                queue,
+                buffer_map.buffer.wrapper().mem_ptr(),
+                ffi::CL_TRUE,
+                access.flags(),
+                offset * size_of_t,
+                len * size_of_t,
+                0,
+                std::ptr::null(),
+                &mut event_ptr,
+                &mut status,
+            )
+        };
+        check_status(status)?;
+        *buffer_map.mapped.write().unwrap() = Some(MappedPtr(ptr));
+        unsafe { Event::new(event_ptr, vec![]) }.wait()?;
+        Ok(buffer_map)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_mapped(&self) -> bool {
+        self.mapped.read().unwrap().is_some()
+    }
+
+    pub fn is_shared(&self) -> bool {
+        self.shared.load(Ordering::SeqCst)
+    }
+
+    fn read_lock(&self) -> OutputEx<RwLockReadGuard<Option<MappedPtr>>> {
+        if !self.access.is_readable() {
+            return Err(BufferError::MapNotReadable(self.access).into());
+        }
+        let guard = self.mapped.read().unwrap();
+        if guard.is_none() {
+            return Err(BufferError::NotMapped.into());
+        }
+        Ok(guard)
+    }
+
+    fn write_lock(&self) -> OutputEx<RwLockWriteGuard<Option<MappedPtr>>> {
+        let guard = self.mapped.write().unwrap();
+        if self.is_shared() {
+            return Err(BufferError::MapShared.into());
+        }
+        if guard.is_none() {
+            return Err(BufferError::NotMapped.into());
+        }
+        Ok(guard)
+    }
+
+    /// Copies the mapped numbers into a new array.
+    pub fn to_array(&self) -> OutputEx<ArrayEx> {
+        let guard = self.read_lock()?;
+        let ptr = guard.as_ref().unwrap().0;
+        let number_type = self.number_type();
+        Ok(apply_number_type!(
+            number_type,
+            _mapped_to_array,
+            [ptr, self.len]
+        ))
+    }
+
+    /// Overwrites the mapped numbers starting at `offset` with `array`.
+    pub fn write_array(&self, offset: usize, array: &ArrayEx) -> OutputEx<()> {
+        if !self.access.is_writable() {
+            return Err(BufferError::MapNotWritable(self.access).into());
+        }
+        let number_type = self.number_type();
+        number_type.type_check(array.number_type())?;
+        let guard = self.write_lock()?;
+        let ptr = guard.as_ref().unwrap().0;
+        let rt_list = array.read_lock();
+        BoundsError::check_range(offset, rt_list.len(), self.len)?;
+        apply_number_type!(
+            number_type,
+            _write_mapped,
+            [ptr, self.len, offset, &rt_list]
+        );
+        Ok(())
+    }
+
+    /// Marks the mapping as shared so it is never written to or unmapped
+    /// before the `BufferMap` itself is dropped.
+    fn share(&self) -> OutputEx<()> {
+        let _guard = self.read_lock()?;
+        self.shared.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Returns the mapped bytes. Must only be called after `share`.
+    fn shared_bytes(&self) -> &[u8] {
+        let guard = self.mapped.read().unwrap();
+        let ptr = guard.as_ref().unwrap().0;
+        let byte_len = self.len * self.number_type().size_of();
+        unsafe { std::slice::from_raw_parts(ptr as *const u8, byte_len) }
+    }
+
+    /// Unmaps the region. Blocks until done.
+    pub fn unmap(&self) -> OutputEx<()> {
+        let mut guard = self.write_lock()?;
+        let mapped_ptr = guard.take().unwrap();
+        self.enqueue_unmap(mapped_ptr)?.wait()
+    }
+
+    fn enqueue_unmap(&self, mapped_ptr: MappedPtr) -> OutputEx<Event> {
+        let mut event_ptr: cl_event = std::ptr::null_mut();
+        let status = unsafe {
+            ffi::clEnqueueUnmapMemObject(
+                self.queue,
+                self.buffer.wrapper().mem_ptr(),
+                mapped_ptr.0,
+                0,
+                std::ptr::null(),
+                &mut event_ptr,
+            )
+        };
+        check_status(status)?;
+        Ok(unsafe { Event::new(event_ptr, vec![]) })
+    }
+}
+
+fn _mapped_to_array<T: NumberEx>(ptr: *mut libc::c_void, len: usize) -> ArrayEx {
+    let data: &[T] = unsafe { std::slice::from_raw_parts(ptr as *const T, len) };
+    ArrayEx::from(RuntimeNumberList::from_vec(data.to_vec()))
+}
+
+fn _write_mapped<T: NumberEx>(
+    ptr: *mut libc::c_void,
+    len: usize,
+    offset: usize,
+    rt_list: &RuntimeNumberList,
+) {
+    let data: &mut [T] = unsafe { std::slice::from_raw_parts_mut(ptr as *mut T, len) };
+    let src: &[T] = rt_list.force_as_slice();
+    data[offset..offset + src.len()].copy_from_slice(src);
+}
+
+impl NumberTyped for BufferMap {
+    fn number_type(&self) -> NumberType {
+        self.buffer.number_type()
+    }
+}
+
+impl Drop for BufferMap {
+    fn drop(&mut self) {
+        // drop runs on whichever scheduler thread collects the resource, so don't wait
+        // for the unmap; OpenCL keeps the buffer alive until it is done.
+        if let Some(mapped_ptr) = self.mapped.write().unwrap().take() {
+            if self.enqueue_unmap(mapped_ptr).is_ok() {
+                unsafe {
+                    ffi::clFlush(self.queue);
+                }
+            }
+        }
+        unsafe {
+            ffi::clReleaseCommandQueue(self.queue);
+        }
+    }
+}
+
+impl fmt::Debug for BufferMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "BufferMap {{ type: {:?}, access: {:?}, len: {} }}",
+            self.number_type(),
+            self.access,
+            self.len
+        )
+    }
+}
+
+#[derive(NifStruct)]
+#[must_use]
+#[module = "OpenCL.BufferMap"]
+pub struct BufferMapEx {
+    __native__: ResourceArc<BufferMap>,
+}
+
+impl fmt::Debug for BufferMapEx {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BufferMapEx {{ native: {:?} }}", *self.__native__)
+    }
+}
+
+impl From<BufferMap> for BufferMapEx {
+    fn from(buffer_map: BufferMap) -> BufferMapEx {
+        BufferMapEx {
+            __native__: ResourceArc::new(buffer_map),
+        }
+    }
+}
+
+impl BufferMapEx {
+    pub fn buffer_map(&self) -> &BufferMap {
+        &self.__native__
+    }
+
+    /// A binary that points directly at the mapped memory without copying.
+    ///
+    /// The mapping can no longer be written to or explicitly unmapped afterwards;
+    /// it is unmapped once the binary and the `BufferMapEx` are garbage collected.
+    pub fn to_shared_binary<'a>(&self, env: Env<'a>) -> OutputEx<Binary<'a>> {
+        self.buffer_map().share()?;
+        Ok(self
+            .__native__
+            .make_binary(env, |buffer_map| buffer_map.shared_bytes()))
+    }
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn buffer_map_self_to_array(buffer_map: BufferMapEx) -> OutputEx<ArrayEx> {
+    buffer_map.buffer_map().to_array()
+}
+
+#[rustler::nif]
+pub fn buffer_map_self_to_shared_binary<'a>(
+    env: Env<'a>,
+    buffer_map: BufferMapEx,
+) -> OutputEx<Binary<'a>> {
+    buffer_map.to_shared_binary(env)
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn buffer_map_self_write_array(
+    buffer_map: BufferMapEx,
+    offset: usize,
+    array: ArrayEx,
+) -> OutputEx<()> {
+    buffer_map.buffer_map().write_array(offset, &array)
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn buffer_map_self_unmap(buffer_map: BufferMapEx) -> OutputEx<()> {
+    buffer_map.buffer_map().unmap()
+}
+
+#[rustler::nif]
+pub fn buffer_map_self_is_mapped(buffer_map: BufferMapEx) -> bool {
+    buffer_map.buffer_map().is_mapped()
+}
+
+#[rustler::nif]
+pub fn buffer_map_self_length(buffer_map: BufferMapEx) -> usize {
+    buffer_map.buffer_map().len()
+}
+
+#[rustler::nif]
+pub fn buffer_map_self_number_type(buffer_map: BufferMapEx) -> NumberType {
+    buffer_map.buffer_map().number_type()
+}
@@ -0,0 +1,56 @@
+use rustler::NifMap;
+
+/// The compiler options a program is built with.
+///
+/// Elixir builds this map from `OpenCL.BuildOptions`.
+#[derive(NifMap, Debug, PartialEq, Eq, Clone, Default)]
+pub struct BuildOptionsEx {
+    defines: Vec<(String, Option<String>)>,
+    include_paths: Vec<String>,
+    cl_std: Option<String>,
+    fast_relaxed_math: bool,
+    extra: Vec<String>,
+}
+
+/// Quotes a value with whitespace, quotes or backslashes, escaping the quotes and
+/// backslashes so the compiler reads the value back unchanged.
+fn quote_if_needed(value: &str) -> String {
+    let needs_quotes = |c: char| c.is_whitespace() || c == '"' || c == '\\';
+    if !value.contains(needs_quotes) {
+        return value.to_owned();
+    }
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+impl BuildOptionsEx {
+    /// Turns the options into the string passed to `clBuildProgram`.
+    pub fn to_options_string(&self) -> String {
+        let mut opts: Vec<String> = Vec::new();
+        for (name, value) in self.defines.iter() {
+            match value {
+                Some(value) => opts.push(format!("-D {}={}", name, quote_if_needed(value))),
+                None => opts.push(format!("-D {}", name)),
+            }
+        }
+        for path in self.include_paths.iter() {
+            opts.push(format!("-I {}", quote_if_needed(path)));
+        }
+        if let Some(cl_std) = &self.cl_std {
+            opts.push(format!("-cl-std={}", cl_std));
+        }
+        if self.fast_relaxed_math {
+            opts.push("-cl-fast-relaxed-math".to_owned());
+        }
+        opts.extend(self.extra.iter().cloned());
+        opts.join(" ")
+    }
+}
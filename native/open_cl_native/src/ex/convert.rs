@@ -1,6 +1,6 @@
 
 use rustler::{Decoder, ListIterator, Error};
-use crate::{NumberEx, RuntimeNumberList};
+use crate::{NumberEx, NumberTypeError, OutputEx, RuntimeNumberList};
 
 pub fn list_iterator_to_vec<'a, T: NumberEx + Decoder<'a>>(iter: ListIterator<'a>) -> Result<Vec<T>, Error> {
     iter.map(|x| x.decode::<T>()).collect()
@@ -9,4 +9,25 @@ pub fn list_iterator_to_vec<'a, T: NumberEx + Decoder<'a>>(iter: ListIterator<'a
 pub fn list_iterator_to_rt_list<'a, T: NumberEx + Decoder<'a>>(iter: ListIterator<'a>) -> Result<RuntimeNumberList, Error> {
     let data: Vec<T> = list_iterator_to_vec(iter)?;
     Ok(RuntimeNumberList::from_vec(data))
-}
\ No newline at end of file
+}
+
+/// Decodes `bytes` as raw little-endian numbers of type `T`.
+pub fn le_bytes_to_rt_list<T: NumberEx>(bytes: &[u8]) -> OutputEx<RuntimeNumberList> {
+    let size = std::mem::size_of::<T>();
+    if bytes.len() % size != 0 {
+        return Err(NumberTypeError::InvalidByteSize(T::number_type_of(), bytes.len()).into());
+    }
+    let data: Vec<T> = bytes.chunks_exact(size).map(T::read_le).collect();
+    Ok(RuntimeNumberList::from_vec(data))
+}
+
+/// Writes the numbers of `rt_list` into `out` as raw little-endian bytes.
+///
+/// `out` must be exactly `rt_list.len() * size_of::<T>()` bytes long.
+pub fn rt_list_to_le_bytes<T: NumberEx>(rt_list: &RuntimeNumberList, out: &mut [u8]) {
+    let size = std::mem::size_of::<T>();
+    let data: &[T] = rt_list.force_as_slice();
+    for (num, chunk) in data.iter().zip(out.chunks_exact_mut(size)) {
+        num.write_le(chunk);
+    }
+}
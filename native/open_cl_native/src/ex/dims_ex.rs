@@ -1,5 +1,6 @@
 use opencl_core::ll::Dims;
-use rustler::{NifTuple, NifUntaggedEnum};
+use rustler::types::tuple::{get_tuple, make_tuple};
+use rustler::{Decoder, Encoder, Env, NifResult, NifTuple, NifUntaggedEnum, Term};
 
 pub trait Dimension {
     fn product(&self) -> usize;
@@ -55,6 +56,27 @@ impl Dimension for DimsEx {
     }
 }
 
+impl DimsEx {
+    pub fn n_dimensions(&self) -> u32 {
+        match self {
+            DimsEx::LoneNum(..) => 1,
+            DimsEx::One(..) => 1,
+            DimsEx::Two(..) => 2,
+            DimsEx::Three(..) => 3,
+        }
+    }
+
+    /// The dims as a fixed size array padded with `fill` for use with raw OpenCL calls.
+    pub fn to_array(&self, fill: usize) -> [usize; 3] {
+        match *self {
+            DimsEx::LoneNum(x) => [x, fill, fill],
+            DimsEx::One(OneDim(x)) => [x, fill, fill],
+            DimsEx::Two(TwoDims(x, y)) => [x, y, fill],
+            DimsEx::Three(ThreeDims(x, y, z)) => [x, y, z],
+        }
+    }
+}
+
 impl From<DimsEx> for Dims {
     fn from(dims: DimsEx) -> Dims {
         match dims {
@@ -75,3 +97,61 @@ impl From<Dims> for DimsEx {
         }
     }
 }
+
+/// The `{row_pitch, slice_pitch}` (in elements) of a rectangular region of a buffer.
+///
+/// A pitch of 0 lets OpenCL compute a tightly packed pitch from the region.
+#[derive(NifTuple, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct PitchesEx(pub usize, pub usize);
+
+/// A tuple of any number of sizes such as the shape `{2, 3, 4}` of an array.
+///
+/// Also used for axis permutations, e.g. `{1, 0}`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ShapeEx(pub Vec<usize>);
+
+impl Dimension for ShapeEx {
+    /// Saturates at `usize::MAX`; see `checked_product`.
+    fn product(&self) -> usize {
+        self.checked_product().unwrap_or(std::usize::MAX)
+    }
+
+    fn matches_length(&self, len: usize) -> bool {
+        self.checked_product() == Some(len)
+    }
+}
+
+impl ShapeEx {
+    pub fn as_slice(&self) -> &[usize] {
+        &self.0[..]
+    }
+
+    /// The number of elements of an array of this shape, or `None` on overflow.
+    pub fn checked_product(&self) -> Option<usize> {
+        self.0.iter().try_fold(1usize, |acc, n| acc.checked_mul(*n))
+    }
+
+    /// The strides (in elements) of a row-major array of this shape.
+    pub fn row_major_strides(&self) -> ShapeEx {
+        let mut strides = vec![1; self.0.len()];
+        for i in (1..self.0.len()).rev() {
+            strides[i - 1] = strides[i] * self.0[i];
+        }
+        ShapeEx(strides)
+    }
+}
+
+impl Encoder for ShapeEx {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        let terms: Vec<Term<'a>> = self.0.iter().map(|n| n.encode(env)).collect();
+        make_tuple(env, &terms[..])
+    }
+}
+
+impl<'a> Decoder<'a> for ShapeEx {
+    fn decode(term: Term<'a>) -> NifResult<ShapeEx> {
+        let sizes: NifResult<Vec<usize>> =
+            get_tuple(term)?.into_iter().map(|t| t.decode()).collect();
+        Ok(ShapeEx(sizes?))
+    }
+}
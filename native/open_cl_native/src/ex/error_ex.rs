@@ -1,10 +1,14 @@
 use opencl_core::Error as OpenCLError;
+use opencl_core::ll::ffi::{cl_int, CL_SUCCESS};
 use opencl_core::ll::StatusCodeError;
 
 use rustler::Error as RustlerError;
 use rustler::{Encoder, Env, Term, NifStruct, NifUnitEnum};
 
+use crate::ex::array_ex::ArrayError;
 use crate::ex::buffer_ex::BufferError;
+use crate::ex::kernel_ex::KernelError;
+use crate::ex::program_ex::{BuildErrorEx, ProgramError};
 use crate::number::NumberTypeError;
 
 pub type OutputEx<T> = Result<T, ErrorEx>;
@@ -15,11 +19,72 @@ pub enum ErrorEx {
     #[fail(display = "{:?}", _0)]
     OpenCLError(OpenCLError),
 
+    #[fail(display = "{:?}", _0)]
+    ArrayError(ArrayError),
+
     #[fail(display = "{:?}", _0)]
     BufferError(BufferError),
 
+    #[fail(display = "{:?}", _0)]
+    BoundsError(BoundsError),
+
     #[fail(display = "{:?}", _0)]
     NumberTypeError(NumberTypeError),
+
+    #[fail(display = "{:?}", _0)]
+    KernelError(KernelError),
+
+    #[fail(display = "{:?}", _0)]
+    ProgramError(ProgramError),
+
+    #[fail(display = "{:?}", _0)]
+    BuildError(BuildErrorEx),
+}
+
+/// An index or range that does not fit in an array or buffer.
+#[derive(Debug, Fail, PartialEq, Eq, Clone)]
+pub enum BoundsError {
+    #[fail(display = "Index {} is out of bounds for length {}", _0, _1)]
+    IndexOutOfBounds(usize, usize),
+
+    #[fail(display = "Range {}..{} is out of bounds for length {}", _0, _1, _2)]
+    RangeOutOfBounds(usize, usize, usize),
+
+    #[fail(
+        display = "Rect region {:?} at origin {:?} is out of bounds for {} bytes",
+        _1, _0, _2
+    )]
+    RectOutOfBounds([usize; 3], [usize; 3], usize),
+}
+
+impl BoundsError {
+    pub fn check_index(index: usize, len: usize) -> OutputEx<()> {
+        if index >= len {
+            return Err(BoundsError::IndexOutOfBounds(index, len).into());
+        }
+        Ok(())
+    }
+
+    /// Checks that `count` items starting at `offset` fit in `len`.
+    pub fn check_range(offset: usize, count: usize, len: usize) -> OutputEx<()> {
+        match offset.checked_add(count) {
+            Some(end) if end <= len => Ok(()),
+            _ => {
+                let end = offset.saturating_add(count);
+                Err(BoundsError::RangeOutOfBounds(offset, end, len).into())
+            }
+        }
+    }
+}
+
+/// Turns the status code returned by a raw OpenCL FFI call into an `OutputEx`.
+pub fn check_status(status: cl_int) -> OutputEx<()> {
+    if status == CL_SUCCESS as cl_int {
+        Ok(())
+    } else {
+        let err = OpenCLError::StatusCodeError(StatusCodeError::new(status));
+        Err(err.into())
+    }
 }
 
 impl From<ErrorEx> for RustlerError {
@@ -39,14 +104,29 @@ macro_rules! impl_error_ex_conv {
 }
 
 impl_error_ex_conv!(OpenCLError);
+impl_error_ex_conv!(ArrayError);
 impl_error_ex_conv!(BufferError);
+impl_error_ex_conv!(BoundsError);
 impl_error_ex_conv!(NumberTypeError);
+impl_error_ex_conv!(KernelError);
+impl_error_ex_conv!(ProgramError);
+
+impl From<BuildErrorEx> for ErrorEx {
+    fn from(err: BuildErrorEx) -> ErrorEx {
+        ErrorEx::BuildError(err)
+    }
+}
 
 impl Encoder for ErrorEx {
     fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
         match self {
             ErrorEx::NumberTypeError(err) => format!("{:?}", err).encode(env),
+            ErrorEx::ArrayError(err) => format!("{:?}", err).encode(env),
             ErrorEx::BufferError(err) => format!("{:?}", err).encode(env),
+            ErrorEx::BoundsError(err) => format!("{:?}", err).encode(env),
+            ErrorEx::KernelError(err) => format!("{:?}", err).encode(env),
+            ErrorEx::ProgramError(err) => format!("{:?}", err).encode(env),
+            ErrorEx::BuildError(err) => err.encode(env),
             ErrorEx::OpenCLError(OpenCLError::StatusCodeError(err)) => {
                 let err_ex = StatusCodeErrorEx::new(*err);
                 err_ex.encode(env)
@@ -56,7 +136,7 @@ impl Encoder for ErrorEx {
     }
 }
 
-#[derive(NifUnitEnum, Debug)]
+#[derive(NifUnitEnum, Debug, PartialEq, Eq, Clone, Copy)]
 pub enum OnlyTrue {
     True
 }
@@ -0,0 +1,262 @@
+use std::fmt;
+
+use opencl_core::ll::ffi::{self, cl_event, cl_int};
+use rustler::env::OwnedEnv;
+use rustler::resource::ResourceArc;
+use rustler::types::Pid;
+use rustler::{Encoder, Env, NifStruct, NifUnitEnum, Term};
+
+use crate::atoms;
+use crate::ex::error_ex::check_status;
+use crate::{BufferEx, OutputEx};
+
+type EventCallback = Box<dyn FnOnce(cl_int) + Send + 'static>;
+
+#[derive(NifUnitEnum, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum EventStatusEx {
+    Queued,
+    Submitted,
+    Running,
+    Complete,
+}
+
+impl EventStatusEx {
+    pub fn from_cl_status(status: cl_int) -> OutputEx<EventStatusEx> {
+        // negative execution statuses are error codes.
+        check_status(status.min(0))?;
+        let status_ex = match status {
+            s if s == ffi::CL_QUEUED as cl_int => EventStatusEx::Queued,
+            s if s == ffi::CL_SUBMITTED as cl_int => EventStatusEx::Submitted,
+            s if s == ffi::CL_RUNNING as cl_int => EventStatusEx::Running,
+            _ => EventStatusEx::Complete,
+        };
+        Ok(status_ex)
+    }
+}
+
+/// Device timestamps (in nanoseconds) of an event's command.
+///
+/// Only available when the command queue was created with `:profiling_enabled`.
+#[derive(NifStruct, Debug, PartialEq, Eq, Clone, Copy)]
+#[module = "OpenCL.ProfilingInfo"]
+pub struct ProfilingInfoEx {
+    queued: u64,
+    submitted: u64,
+    start: u64,
+    end: u64,
+}
+
+/// An owned raw event plus the buffers the enqueued command is using.
+///
+/// The event is released on drop.
+pub struct Event {
+    ptr: cl_event,
+    _buffers: Vec<BufferEx>,
+}
+
+unsafe impl Send for Event {}
+unsafe impl Sync for Event {}
+
+impl Event {
+    /// Takes ownership of `ptr`. The caller must not release `ptr` itself.
+    pub unsafe fn new(ptr: cl_event, buffers: Vec<BufferEx>) -> Event {
+        Event {
+            ptr,
+            _buffers: buffers,
+        }
+    }
+
+    pub fn ptr(&self) -> cl_event {
+        self.ptr
+    }
+
+    pub fn wait(&self) -> OutputEx<()> {
+        check_status(unsafe { ffi::clWaitForEvents(1, &self.ptr) })?;
+        self.status().map(|_| ())
+    }
+
+    pub fn status(&self) -> OutputEx<EventStatusEx> {
+        let mut status: cl_int = 0;
+        let info_status = unsafe {
+            ffi::clGetEventInfo(
+                self.ptr,
+                ffi::CL_EVENT_COMMAND_EXECUTION_STATUS,
+                std::mem::size_of::<cl_int>(),
+                &mut status as *mut cl_int as *mut libc::c_void,
+                std::ptr::null_mut(),
+            )
+        };
+        check_status(info_status)?;
+        EventStatusEx::from_cl_status(status)
+    }
+
+    fn profiling_counter(&self, param: ffi::cl_uint) -> OutputEx<u64> {
+        let mut value: u64 = 0;
+        let status = unsafe {
+            ffi::clGetEventProfilingInfo(
+                self.ptr,
+                param,
+                std::mem::size_of::<u64>(),
+                &mut value as *mut u64 as *mut libc::c_void,
+                std::ptr::null_mut(),
+            )
+        };
+        check_status(status)?;
+        Ok(value)
+    }
+
+    pub fn profiling_info(&self) -> OutputEx<ProfilingInfoEx> {
+        Ok(ProfilingInfoEx {
+            queued: self.profiling_counter(ffi::CL_PROFILING_COMMAND_QUEUED)?,
+            submitted: self.profiling_counter(ffi::CL_PROFILING_COMMAND_SUBMIT)?,
+            start: self.profiling_counter(ffi::CL_PROFILING_COMMAND_START)?,
+            end: self.profiling_counter(ffi::CL_PROFILING_COMMAND_END)?,
+        })
+    }
+
+    /// Waits for the command and returns its profiling info.
+    pub fn wait_for_profiling_info(&self) -> OutputEx<ProfilingInfoEx> {
+        self.wait()?;
+        self.profiling_info()
+    }
+
+    /// Calls `callback` with the event's final execution status once the command
+    /// has completed or failed. The callback runs on a thread owned by the OpenCL driver.
+    pub fn on_complete<F>(&self, callback: F) -> OutputEx<()>
+    where
+        F: FnOnce(cl_int) + Send + 'static,
+    {
+        let boxed: Box<EventCallback> = Box::new(Box::new(callback));
+        let user_data = Box::into_raw(boxed) as *mut libc::c_void;
+        let status = unsafe {
+            ffi::clSetEventCallback(
+                self.ptr,
+                ffi::CL_COMPLETE as cl_int,
+                Some(event_callback),
+                user_data,
+            )
+        };
+        if let Err(e) = check_status(status) {
+            // the callback was never registered so it will never run; reclaim it.
+            let _ = unsafe { Box::from_raw(user_data as *mut EventCallback) };
+            return Err(e);
+        }
+        Ok(())
+    }
+}
+
+extern "C" fn event_callback(_event: cl_event, status: cl_int, user_data: *mut libc::c_void) {
+    let callback: Box<EventCallback> = unsafe { Box::from_raw(user_data as *mut EventCallback) };
+    callback(status)
+}
+
+impl Drop for Event {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::clReleaseEvent(self.ptr);
+        }
+    }
+}
+
+impl fmt::Debug for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Event {{ ptr: {:?} }}", self.ptr)
+    }
+}
+
+#[derive(NifStruct)]
+#[must_use]
+#[module = "OpenCL.Event"]
+pub struct EventEx {
+    __native__: ResourceArc<Event>,
+}
+
+impl Clone for EventEx {
+    fn clone(&self) -> EventEx {
+        EventEx {
+            __native__: self.__native__.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for EventEx {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EventEx {{ native: {:?} }}", *self.__native__)
+    }
+}
+
+impl From<Event> for EventEx {
+    fn from(event: Event) -> EventEx {
+        EventEx {
+            __native__: ResourceArc::new(event),
+        }
+    }
+}
+
+impl EventEx {
+    pub fn event(&self) -> &Event {
+        &self.__native__
+    }
+
+    /// Like `Event::on_complete`, but the callback also holds a reference to the event
+    /// so the event (and its buffers) live until the callback has run.
+    pub fn on_complete<F>(&self, callback: F) -> OutputEx<()>
+    where
+        F: FnOnce(cl_int) + Send + 'static,
+    {
+        let keep_alive = self.clone();
+        self.event().on_complete(move |status| {
+            callback(status);
+            drop(keep_alive);
+        })
+    }
+
+    /// Sends `{:opencl_complete, reference, result}` to `pid` once the command has finished.
+    ///
+    /// `result` builds the result term from the command's final execution status.
+    pub fn notify<'a, F>(&self, pid: Pid, reference: Term<'a>, result: F) -> OutputEx<()>
+    where
+        F: for<'b> FnOnce(Env<'b>, cl_int) -> Term<'b> + Send + 'static,
+    {
+        let mut owned_env = OwnedEnv::new();
+        let saved_reference = owned_env.save(reference);
+        self.on_complete(move |status| {
+            owned_env.send_and_clear(&pid, |env| {
+                let reference = saved_reference.load(env);
+                (atoms::opencl_complete(), reference, result(env, status)).encode(env)
+            });
+        })
+    }
+}
+
+/// Encodes a final execution status as `:ok` or `{:error, reason}`.
+pub fn encode_status_result<'a>(env: Env<'a>, status: cl_int) -> Term<'a> {
+    match check_status(status.min(0)) {
+        Ok(()) => atoms::ok().encode(env),
+        Err(e) => (atoms::error(), e).encode(env),
+    }
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn event_self_wait(event: EventEx) -> OutputEx<()> {
+    event.event().wait()
+}
+
+#[rustler::nif]
+pub fn event_self_status(event: EventEx) -> OutputEx<EventStatusEx> {
+    event.event().status()
+}
+
+#[rustler::nif]
+pub fn event_self_profiling_info(event: EventEx) -> OutputEx<ProfilingInfoEx> {
+    event.event().profiling_info()
+}
+
+#[rustler::nif]
+pub fn event_self_on_complete<'a>(event: EventEx, pid: Pid, message: Term<'a>) -> OutputEx<()> {
+    let mut owned_env = OwnedEnv::new();
+    let saved_message = owned_env.save(message);
+    event.on_complete(move |_status| {
+        owned_env.send_and_clear(&pid, |env| saved_message.load(env));
+    })
+}
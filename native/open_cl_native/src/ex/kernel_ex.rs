@@ -1,15 +1,557 @@
 // use std::marker::PhantomData;
-use rustler::{Encoder, NifMap, NifStruct, NifUntaggedEnum};
+use std::ffi::CString;
+use std::fmt;
+use std::sync::{Mutex, MutexGuard};
 
-use opencl_core::ll::KernelArg;
-use opencl_core::{ClNumber, CommandQueueOptions, KernelOpArg, KernelOperation, Work};
+use rustler::resource::ResourceArc;
+use rustler::{Encoder, NifMap, NifStruct, NifTuple, NifUnitEnum, NifUntaggedEnum};
 
-use crate::{BufferEx, DimsEx, NumEx, NumberEx, NumberType, NumberTyped, NumberTypedT, OutputEx};
+use opencl_core::ll::ffi::{self, cl_event, cl_int, cl_kernel, cl_mem, cl_program, cl_uint};
+use opencl_core::ll::{KernelArg, SizeAndPtr};
+use opencl_core::{CommandQueueOptions, Work};
 
+use crate::ex::error_ex::check_status;
+use crate::ex::event_ex::Event;
+use crate::ex::program_ex::read_info_string;
+use crate::{
+    BufferEx, DimsEx, NumEx, NumberEx, NumberType, NumberTyped, OutputEx, ProgramEx, SessionEx,
+    TypedNumEx,
+};
+
+#[derive(Debug, Fail, PartialEq, Eq, Clone)]
+pub enum KernelError {
+    #[fail(display = "Invalid kernel name - {:?}", _0)]
+    InvalidName(String),
+
+    #[fail(display = "Kernel arg index {} is out of range for {} args", _0, _1)]
+    ArgIndexOutOfRange(usize, usize),
+
+    #[fail(display = "Kernel arg {} has not been set", _0)]
+    UnsetArg(usize),
+
+    #[fail(display = "Vector type {:?} takes {} components - got {}", _0, _1, _2)]
+    VectorLength(VectorTypeEx, usize, usize),
+}
+
+#[derive(NifUnitEnum, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum AddressQualifierEx {
+    Global,
+    Local,
+    Constant,
+    Private,
+}
+
+impl AddressQualifierEx {
+    fn from_cl_type(qualifier: cl_uint) -> AddressQualifierEx {
+        match qualifier {
+            q if q == ffi::CL_KERNEL_ARG_ADDRESS_GLOBAL => AddressQualifierEx::Global,
+            q if q == ffi::CL_KERNEL_ARG_ADDRESS_LOCAL => AddressQualifierEx::Local,
+            q if q == ffi::CL_KERNEL_ARG_ADDRESS_CONSTANT => AddressQualifierEx::Constant,
+            _ => AddressQualifierEx::Private,
+        }
+    }
+}
+
+#[derive(NifMap, Debug, Clone)]
+pub struct KernelArgInfoEx {
+    name: String,
+    type_name: String,
+    address_qualifier: AddressQualifierEx,
+}
+
+/// An owned raw kernel. The kernel is released on drop.
+pub struct Kernel {
+    ptr: cl_kernel,
+}
+
+unsafe impl Send for Kernel {}
+unsafe impl Sync for Kernel {}
+
+impl Kernel {
+    pub fn create(session: &SessionEx, name: &str) -> OutputEx<Kernel> {
+        Kernel::create_from_program(session.program_ptr(), name)
+    }
+
+    pub fn create_from_program(program: cl_program, name: &str) -> OutputEx<Kernel> {
+        let c_name = CString::new(name).map_err(|_| KernelError::InvalidName(name.to_owned()))?;
+        let mut status: cl_int = 0;
+        let ptr = unsafe { ffi::clCreateKernel(program, c_name.as_ptr(), &mut status) };
+        check_status(status)?;
+        Ok(Kernel { ptr })
+    }
+
+    pub fn ptr(&self) -> cl_kernel {
+        self.ptr
+    }
+
+    pub fn name(&self) -> OutputEx<String> {
+        read_info_string(|size, value, size_ret| unsafe {
+            ffi::clGetKernelInfo(
+                self.ptr,
+                ffi::CL_KERNEL_FUNCTION_NAME,
+                size,
+                value,
+                size_ret,
+            )
+        })
+    }
+
+    pub fn num_args(&self) -> OutputEx<usize> {
+        let mut num_args: cl_uint = 0;
+        let status = unsafe {
+            ffi::clGetKernelInfo(
+                self.ptr,
+                ffi::CL_KERNEL_NUM_ARGS,
+                std::mem::size_of::<cl_uint>(),
+                &mut num_args as *mut cl_uint as *mut libc::c_void,
+                std::ptr::null_mut(),
+            )
+        };
+        check_status(status)?;
+        Ok(num_args as usize)
+    }
+
+    fn arg_info_string(&self, index: usize, param: cl_uint) -> OutputEx<String> {
+        read_info_string(|size, value, size_ret| unsafe {
+            ffi::clGetKernelArgInfo(self.ptr, index as cl_uint, param, size, value, size_ret)
+        })
+    }
+
+    pub fn arg_info(&self, index: usize) -> OutputEx<KernelArgInfoEx> {
+        let mut qualifier: cl_uint = 0;
+        let status = unsafe {
+            ffi::clGetKernelArgInfo(
+                self.ptr,
+                index as cl_uint,
+                ffi::CL_KERNEL_ARG_ADDRESS_QUALIFIER,
+                std::mem::size_of::<cl_uint>(),
+                &mut qualifier as *mut cl_uint as *mut libc::c_void,
+                std::ptr::null_mut(),
+            )
+        };
+        check_status(status)?;
+        Ok(KernelArgInfoEx {
+            name: self.arg_info_string(index, ffi::CL_KERNEL_ARG_NAME)?,
+            type_name: self.arg_info_string(index, ffi::CL_KERNEL_ARG_TYPE_NAME)?,
+            address_qualifier: AddressQualifierEx::from_cl_type(qualifier),
+        })
+    }
+
+    pub fn set_arg(&self, index: usize, arg: &ArgEx) -> OutputEx<()> {
+        let status = match arg {
+            ArgEx::Buffer(buf) => {
+                let mem: cl_mem = buf.wrapper().mem_ptr();
+                unsafe {
+                    ffi::clSetKernelArg(
+                        self.ptr,
+                        index as cl_uint,
+                        std::mem::size_of::<cl_mem>(),
+                        &mem as *const cl_mem as *const libc::c_void,
+                    )
+                }
+            }
+            ArgEx::Num(num) => self.set_value_arg(index, &self.typed_num_arg(index, num)?),
+            ArgEx::TypedNum(typed) => self.set_value_arg(index, &typed.num()?),
+            ArgEx::Vector(vector) => self.set_value_arg(index, &vector.to_cl_vector()?),
+            ArgEx::Local(local) => unsafe {
+                ffi::clSetKernelArg(
+                    self.ptr,
+                    index as cl_uint,
+                    local.byte_size(),
+                    std::ptr::null(),
+                )
+            },
+        };
+        check_status(status)
+    }
+
+    /// Casts an untyped number arg to the scalar type the kernel declares for arg
+    /// `index`, erroring if that type can't hold it.
+    ///
+    /// The decoded type is kept if the arg info is unavailable or the arg is not a scalar.
+    fn typed_num_arg(&self, index: usize, num: &NumEx) -> OutputEx<NumEx> {
+        let type_name = self
+            .arg_info_string(index, ffi::CL_KERNEL_ARG_TYPE_NAME)
+            .ok();
+        match type_name.as_ref().and_then(|name| scalar_arg_type(name)) {
+            Some(number_type) => num.exact_cast(number_type),
+            None => Ok(*num),
+        }
+    }
+
+    fn set_value_arg<A: KernelArg>(&self, index: usize, value: &A) -> cl_int {
+        unsafe {
+            let size_and_ptr = value.as_kernel_arg();
+            ffi::clSetKernelArg(
+                self.ptr,
+                index as cl_uint,
+                size_and_ptr.0,
+                size_and_ptr.1 as *const libc::c_void,
+            )
+        }
+    }
+
+    pub fn enqueue(&self, session: &SessionEx, work: &WorkEx) -> OutputEx<cl_event> {
+        let work_dim = work.global_work_size.n_dimensions();
+        let global_size = work.global_work_size.to_array(1);
+        let global_offset = work.global_work_offset.map(|d| d.to_array(0));
+        let local_size = work.local_work_size.map(|d| d.to_array(1));
+        let mut event: cl_event = std::ptr::null_mut();
+        let status = session.with_queue(|queue| unsafe {
+            ffi::clEnqueueNDRangeKernel(
+                queue,
+                self.ptr,
+                work_dim,
+                global_offset
+                    .as_ref()
+                    .map_or(std::ptr::null(), |offset| offset.as_ptr()),
+                global_size.as_ptr(),
+                local_size
+                    .as_ref()
+                    .map_or(std::ptr::null(), |local| local.as_ptr()),
+                0,
+                std::ptr::null(),
+                &mut event,
+            )
+        });
+        check_status(status)?;
+        Ok(event)
+    }
+}
+
+/// The number type of a scalar kernel arg from its OpenCL C type name.
+fn scalar_arg_type(type_name: &str) -> Option<NumberType> {
+    let number_type = match type_name {
+        "char" => NumberType::I8,
+        "uchar" | "unsigned char" => NumberType::U8,
+        "short" => NumberType::I16,
+        "ushort" | "unsigned short" => NumberType::U16,
+        "int" => NumberType::I32,
+        "uint" | "unsigned int" => NumberType::U32,
+        "long" => NumberType::I64,
+        "ulong" | "unsigned long" => NumberType::U64,
+        "half" => NumberType::F16,
+        "float" => NumberType::F32,
+        "double" => NumberType::F64,
+        "size_t" | "uintptr_t" => NumberType::Usize,
+        "ptrdiff_t" | "intptr_t" => NumberType::Isize,
+        _ => return None,
+    };
+    Some(number_type)
+}
+
+impl Drop for Kernel {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::clReleaseKernel(self.ptr);
+        }
+    }
+}
+
+/// A kernel created from a `ProgramEx` along with the args bound to it so far.
+///
+/// The args lock is held while args are set and while the kernel is enqueued so
+/// concurrent launches never see each other's args.
+pub struct BoundKernel {
+    kernel: Kernel,
+    args: Mutex<Vec<Option<ArgEx>>>,
+    _program: ProgramEx,
+}
+
+impl BoundKernel {
+    pub fn new(kernel: Kernel, program: ProgramEx) -> OutputEx<BoundKernel> {
+        let num_args = kernel.num_args()?;
+        Ok(BoundKernel {
+            kernel,
+            args: Mutex::new(vec![None; num_args]),
+            _program: program,
+        })
+    }
+
+    pub fn kernel(&self) -> &Kernel {
+        &self.kernel
+    }
+
+    fn lock_args(&self) -> MutexGuard<Vec<Option<ArgEx>>> {
+        self.args.lock().unwrap()
+    }
+
+    pub fn set_arg(&self, index: usize, arg: ArgEx) -> OutputEx<()> {
+        let mut args = self.lock_args();
+        if index >= args.len() {
+            return Err(KernelError::ArgIndexOutOfRange(index, args.len()).into());
+        }
+        self.kernel.set_arg(index, &arg)?;
+        args[index] = Some(arg);
+        Ok(())
+    }
+
+    /// Enqueues the kernel with its bound args. Every arg must have been set.
+    pub fn enqueue(&self, session: &SessionEx, work: &WorkEx) -> OutputEx<Event> {
+        let args = self.lock_args();
+        let mut buffers: Vec<BufferEx> = Vec::new();
+        for (index, arg) in args.iter().enumerate() {
+            match arg {
+                Some(ArgEx::Buffer(buf)) => buffers.push(buf.clone()),
+                Some(_) => (),
+                None => return Err(KernelError::UnsetArg(index).into()),
+            }
+        }
+        let event = self.kernel.enqueue(session, work)?;
+        Ok(unsafe { Event::new(event, buffers) })
+    }
+}
+
+impl fmt::Debug for BoundKernel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BoundKernel {{ ptr: {:?} }}", self.kernel.ptr())
+    }
+}
+
+#[derive(NifStruct)]
+#[must_use]
+#[module = "OpenCL.Kernel"]
+pub struct KernelEx {
+    __native__: ResourceArc<BoundKernel>,
+}
+
+impl fmt::Debug for KernelEx {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "KernelEx {{ native: {:?} }}", *self.__native__)
+    }
+}
+
+impl From<BoundKernel> for KernelEx {
+    fn from(bound: BoundKernel) -> KernelEx {
+        KernelEx {
+            __native__: ResourceArc::new(bound),
+        }
+    }
+}
+
+impl KernelEx {
+    pub fn bound_kernel(&self) -> &BoundKernel {
+        &self.__native__
+    }
+
+    pub fn arg_info(&self) -> OutputEx<Vec<KernelArgInfoEx>> {
+        let kernel = self.bound_kernel().kernel();
+        (0..kernel.num_args()?)
+            .map(|index| kernel.arg_info(index))
+            .collect()
+    }
+}
+
+#[rustler::nif]
+pub fn kernel_self_name(kernel: KernelEx) -> OutputEx<String> {
+    kernel.bound_kernel().kernel().name()
+}
+
+#[rustler::nif]
+pub fn kernel_self_num_args(kernel: KernelEx) -> OutputEx<usize> {
+    kernel.bound_kernel().kernel().num_args()
+}
+
+#[rustler::nif]
+pub fn kernel_self_arg_info(kernel: KernelEx) -> OutputEx<Vec<KernelArgInfoEx>> {
+    kernel.arg_info()
+}
+
+#[rustler::nif]
+pub fn kernel_self_set_arg(kernel: KernelEx, index: usize, arg: ArgEx) -> OutputEx<()> {
+    kernel.bound_kernel().set_arg(index, arg)
+}
+
+#[derive(NifUnitEnum, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum LocalOnly {
+    Local,
+}
+
+/// `{:local, number_type, count}` - `__local` scratch memory for `count` numbers.
+#[derive(NifTuple, Debug, Clone, Copy)]
+pub struct LocalArgEx {
+    _local: LocalOnly,
+    number_type: NumberType,
+    count: usize,
+}
+
+impl LocalArgEx {
+    pub fn byte_size(&self) -> usize {
+        self.number_type.size_of() * self.count
+    }
+}
+
+macro_rules! vector_types {
+    ($( $variant:ident => ($number_type:ident, $width:expr) ),* $(,)*) => {
+        /// The OpenCL C vector types, e.g. `:float4` or `:uchar16`.
+        #[derive(NifUnitEnum, Debug, PartialEq, Eq, Clone, Copy)]
+        pub enum VectorTypeEx {
+            $( $variant ),*
+        }
+
+        impl VectorTypeEx {
+            pub fn element_type(&self) -> NumberType {
+                match self {
+                    $( VectorTypeEx::$variant => NumberType::$number_type ),*
+                }
+            }
+
+            pub fn width(&self) -> usize {
+                match self {
+                    $( VectorTypeEx::$variant => $width ),*
+                }
+            }
+        }
+    };
+}
+
+vector_types! {
+    Char2 => (I8, 2), Char3 => (I8, 3), Char4 => (I8, 4),
+    Char8 => (I8, 8), Char16 => (I8, 16),
+    Uchar2 => (U8, 2), Uchar3 => (U8, 3), Uchar4 => (U8, 4),
+    Uchar8 => (U8, 8), Uchar16 => (U8, 16),
+    Short2 => (I16, 2), Short3 => (I16, 3), Short4 => (I16, 4),
+    Short8 => (I16, 8), Short16 => (I16, 16),
+    Ushort2 => (U16, 2), Ushort3 => (U16, 3), Ushort4 => (U16, 4),
+    Ushort8 => (U16, 8), Ushort16 => (U16, 16),
+    Int2 => (I32, 2), Int3 => (I32, 3), Int4 => (I32, 4),
+    Int8 => (I32, 8), Int16 => (I32, 16),
+    Uint2 => (U32, 2), Uint3 => (U32, 3), Uint4 => (U32, 4),
+    Uint8 => (U32, 8), Uint16 => (U32, 16),
+    Long2 => (I64, 2), Long3 => (I64, 3), Long4 => (I64, 4),
+    Long8 => (I64, 8), Long16 => (I64, 16),
+    Ulong2 => (U64, 2), Ulong3 => (U64, 3), Ulong4 => (U64, 4),
+    Ulong8 => (U64, 8), Ulong16 => (U64, 16),
+    Float2 => (F32, 2), Float3 => (F32, 3), Float4 => (F32, 4),
+    Float8 => (F32, 8), Float16 => (F32, 16),
+    Double2 => (F64, 2), Double3 => (F64, 3), Double4 => (F64, 4),
+    Double8 => (F64, 8), Double16 => (F64, 16),
+}
+
+#[derive(NifTuple, Debug, Copy, Clone)]
+pub struct TwoComponents(NumEx, NumEx);
+
+#[derive(NifTuple, Debug, Copy, Clone)]
+pub struct ThreeComponents(NumEx, NumEx, NumEx);
+
+#[derive(NifTuple, Debug, Copy, Clone)]
+pub struct FourComponents(NumEx, NumEx, NumEx, NumEx);
+
+#[derive(NifTuple, Debug, Copy, Clone)]
+pub struct EightComponents(NumEx, NumEx, NumEx, NumEx, NumEx, NumEx, NumEx, NumEx);
+
+#[derive(NifTuple, Debug, Copy, Clone)]
+pub struct SixteenComponents(
+    NumEx,
+    NumEx,
+    NumEx,
+    NumEx,
+    NumEx,
+    NumEx,
+    NumEx,
+    NumEx,
+    NumEx,
+    NumEx,
+    NumEx,
+    NumEx,
+    NumEx,
+    NumEx,
+    NumEx,
+    NumEx,
+);
+
+#[derive(NifUntaggedEnum, Debug, Copy, Clone)]
+pub enum VectorComponentsEx {
+    Two(TwoComponents),
+    Three(ThreeComponents),
+    Four(FourComponents),
+    Eight(EightComponents),
+    Sixteen(SixteenComponents),
+}
+
+impl VectorComponentsEx {
+    fn to_vec(&self) -> Vec<NumEx> {
+        match *self {
+            VectorComponentsEx::Two(c) => vec![c.0, c.1],
+            VectorComponentsEx::Three(c) => vec![c.0, c.1, c.2],
+            VectorComponentsEx::Four(c) => vec![c.0, c.1, c.2, c.3],
+            VectorComponentsEx::Eight(c) => vec![c.0, c.1, c.2, c.3, c.4, c.5, c.6, c.7],
+            VectorComponentsEx::Sixteen(c) => vec![
+                c.0, c.1, c.2, c.3, c.4, c.5, c.6, c.7, c.8, c.9, c.10, c.11, c.12, c.13, c.14,
+                c.15,
+            ],
+        }
+    }
+}
+
+/// `{vector_type, components}`, e.g. `{:float4, {1.0, 2.0, 3.0, 4.0}}`.
+#[derive(NifTuple, Debug, Copy, Clone)]
+pub struct VectorArgEx {
+    vector_type: VectorTypeEx,
+    components: VectorComponentsEx,
+}
+
+impl VectorArgEx {
+    pub fn to_cl_vector(&self) -> OutputEx<ClVector> {
+        let components = self.components.to_vec();
+        let width = self.vector_type.width();
+        if components.len() != width {
+            let err = KernelError::VectorLength(self.vector_type, width, components.len());
+            return Err(err.into());
+        }
+        let element_type = self.vector_type.element_type();
+        let mut vector = ClVector {
+            bytes: [0; 128],
+            // 3-component vectors are sized and aligned like 4-component vectors.
+            size: element_type.size_of() * if width == 3 { 4 } else { width },
+        };
+        apply_number_type!(
+            element_type,
+            _write_components,
+            [&mut vector.bytes[..], &components[..]]
+        )?;
+        Ok(vector)
+    }
+}
+
+/// Errors if a component doesn't fit in the element type, like a scalar arg.
+fn _write_components<T: NumberEx>(bytes: &mut [u8], components: &[NumEx]) -> OutputEx<()> {
+    for (index, component) in components.iter().enumerate() {
+        let value: T = component.exact_cast_to()?;
+        unsafe {
+            let ptr = bytes.as_mut_ptr().add(index * std::mem::size_of::<T>()) as *mut T;
+            std::ptr::write_unaligned(ptr, value);
+        }
+    }
+    Ok(())
+}
+
+/// The bytes of an OpenCL vector value, aligned for the largest vector type (`double16`).
+#[repr(C, align(128))]
+pub struct ClVector {
+    bytes: [u8; 128],
+    size: usize,
+}
+
+unsafe impl KernelArg for ClVector {
+    unsafe fn as_kernel_arg(&self) -> SizeAndPtr<*mut libc::c_void> {
+        SizeAndPtr(self.size, self.bytes.as_ptr() as *mut libc::c_void)
+    }
+}
+
+/// A kernel arg. Each arg carries its own number type, so the args of one kernel
+/// can have different types.
+///
+/// An untyped `Num` is cast to the scalar type the kernel declares for it; a
+/// `TypedNum` (`{:i32, 10}` in Elixir) is cast to its tag. Either errors if the
+/// number does not fit.
 #[derive(NifUntaggedEnum, Debug)]
 pub enum ArgEx {
     Buffer(BufferEx),
     Num(NumEx),
+    TypedNum(TypedNumEx),
+    Local(LocalArgEx),
+    Vector(VectorArgEx),
 }
 
 impl Clone for ArgEx {
@@ -17,24 +559,9 @@ impl Clone for ArgEx {
         match self {
             ArgEx::Buffer(buff) => ArgEx::Buffer((*buff).clone()),
             ArgEx::Num(num) => ArgEx::Num(num.clone()),
-        }
-    }
-}
-
-impl ArgEx {
-    fn into_kernel_op_arg<'a, T: KernelArg + ClNumber + NumberTypedT + From<NumEx>>(
-        &'a self,
-    ) -> OutputEx<KernelOpArg<'a, T>> {
-        match self {
-            ArgEx::Buffer(buf) => {
-                let buffer_t = buf.wrapper().buffer()?;
-                Ok(KernelOpArg::Buffer(buffer_t))
-            }
-            ArgEx::Num(num) => {
-                let t = T::number_type_of();
-                t.type_check(num.number_type())?;
-                Ok(KernelOpArg::Num((*num).into()))
-            }
+            ArgEx::TypedNum(typed) => ArgEx::TypedNum(*typed),
+            ArgEx::Local(local) => ArgEx::Local(*local),
+            ArgEx::Vector(vector) => ArgEx::Vector(*vector),
         }
     }
 }
@@ -44,6 +571,9 @@ impl NumberTyped for ArgEx {
         match self {
             ArgEx::Buffer(buf) => buf.number_type(),
             ArgEx::Num(num) => num.number_type(),
+            ArgEx::TypedNum(typed) => typed.number_type(),
+            ArgEx::Local(local) => local.number_type,
+            ArgEx::Vector(vector) => vector.vector_type.element_type(),
         }
     }
 }
@@ -75,6 +605,13 @@ pub struct CommandQueueOptionsEx {
     offset: Option<usize>,
 }
 
+impl CommandQueueOptionsEx {
+    /// The element offset into the buffer, defaulting to 0.
+    pub fn offset_or_zero(opts: &Option<CommandQueueOptionsEx>) -> usize {
+        opts.as_ref().and_then(|o| o.offset).unwrap_or(0)
+    }
+}
+
 impl From<CommandQueueOptionsEx> for CommandQueueOptions {
     fn from(opts: CommandQueueOptionsEx) -> CommandQueueOptions {
         CommandQueueOptions::from(&opts)
@@ -112,29 +649,43 @@ impl KernelOpEx {
 }
 
 impl KernelOpEx {
-    pub fn into_kernel_operation<'a, T: NumberEx + From<NumEx> + KernelArg>(
-        &'a self,
-    ) -> OutputEx<KernelOperation<'a, T>> {
-        let mut op = KernelOperation::new(self.name.as_str()).with_work(self.work.clone());
-        if let Some(opts) = &self.command_queue_opts {
-            op = op.with_command_queue_options(opts.into());
-        };
-        if let Some(ret) = self.returning {
-            op = op.with_returning_arg(ret);
-        };
-        for arg in self.args.iter() {
-            let cl_arg = arg.into_kernel_op_arg()?;
-            op = op.add_arg(cl_arg);
+    /// Enqueues the operation without waiting for it to finish.
+    ///
+    /// The returned event keeps every buffer arg alive until the event itself is dropped.
+    pub fn enqueue(&self, session: &SessionEx) -> OutputEx<Event> {
+        let kernel = Kernel::create(session, self.name.as_str())?;
+        let mut buffers: Vec<BufferEx> = Vec::new();
+        for (index, arg) in self.args.iter().enumerate() {
+            if let ArgEx::Buffer(buf) = arg {
+                buffers.push(buf.clone());
+            }
+            kernel.set_arg(index, arg)?;
         }
-        Ok(op)
+        let event = kernel.enqueue(session, &self.work)?;
+        Ok(unsafe { Event::new(event, buffers) })
+    }
+
+    /// Whether the caller should wait for the operation. Defaults to `true`.
+    pub fn is_blocking(&self) -> bool {
+        self.command_queue_opts
+            .as_ref()
+            .and_then(|opts| opts.is_blocking)
+            .unwrap_or(true)
     }
 }
 
 impl NumberTyped for KernelOpEx {
+    /// The type of the returning arg, else of the first buffer arg, else `U8`.
     fn number_type(&self) -> NumberType {
         self.returning
-            .map(|index| self.args.get(index).map(|arg| arg.number_type()))
-            .unwrap_or(Some(NumberType::U8))
-            .unwrap()
+            .and_then(|index| self.args.get(index))
+            .or_else(|| {
+                self.args.iter().find(|arg| match arg {
+                    ArgEx::Buffer(..) => true,
+                    _ => false,
+                })
+            })
+            .map(|arg| arg.number_type())
+            .unwrap_or(NumberType::U8)
     }
 }
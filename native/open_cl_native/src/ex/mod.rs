@@ -1,4 +1,4 @@
-use opencl_core::{Device, Platform, Session};
+use opencl_core::{Device, Platform};
 
 // HELPERS
 pub mod array_ex;
@@ -11,33 +11,46 @@ pub mod convert;
 
 // OPENCL OBJECT WRAPPERS
 pub mod buffer_ex;
+pub mod buffer_map_ex;
+pub mod build_options_ex;
 pub mod command_queue_props_ex;
 pub mod device_ex;
+pub mod event_ex;
 pub mod kernel_ex;
 pub mod platform_ex;
+pub mod program_cache_ex;
+pub mod program_ex;
 pub mod session_ex;
 
 pub use command_queue_props_ex::CommandQueuePropEx;
 pub use device_ex::DeviceEx;
-pub use error_ex::{ErrorEx, OutputEx};
+pub use error_ex::{BoundsError, ErrorEx, OutputEx};
 pub use platform_ex::PlatformEx;
 pub use wrapper_ex::{WrapperEx, WrapperExResource};
 
-pub use array_ex::{Array, ArrayEx};
+pub use array_ex::{Array, ArrayError, ArrayEx};
 
 pub use buffer_ex::*;
-pub use dims_ex::{Dimension, DimsEx};
+pub use buffer_map_ex::{BufferMap, BufferMapEx, MapAccessEx};
+pub use build_options_ex::BuildOptionsEx;
+pub use dims_ex::{Dimension, DimsEx, PitchesEx, ShapeEx};
+pub use event_ex::{Event, EventEx};
 pub use kernel_ex::*;
 pub use number_ex::*;
 pub use number_list_ex::*;
+pub use program_ex::{Program, ProgramEx};
 
-pub use session_ex::SessionEx;
+pub use session_ex::{SessionEx, SessionWrapper};
 
 pub fn define_resources(env: rustler::Env) -> bool {
     rustler::resource!(WrapperEx<Platform>, env);
     rustler::resource!(WrapperEx<Device>, env);
     rustler::resource!(Array, env);
-    rustler::resource!(WrapperEx<Session>, env);
+    rustler::resource!(SessionWrapper, env);
     rustler::resource!(BufferWrapper, env);
+    rustler::resource!(BufferMap, env);
+    rustler::resource!(Event, env);
+    rustler::resource!(Program, env);
+    rustler::resource!(BoundKernel, env);
     true
 }
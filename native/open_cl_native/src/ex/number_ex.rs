@@ -1,4 +1,4 @@
-use rustler::{NifUntaggedEnum};
+use rustler::{NifTuple, NifUntaggedEnum};
 
 use opencl_core::ll::{ClNumber, KernelArg, SizeAndPtr};
 
@@ -6,7 +6,10 @@ use num_traits::identities::Zero;
 use num_traits::{FromPrimitive, NumCast, ToPrimitive};
 use std::fmt::Debug;
 
-use crate::{NumberType, NumberTyped, NumberTypedT};
+use crate::{
+    CastBounds, CastMode, Elementwise, LeBytes, NumberType, NumberTypeError, NumberTyped,
+    NumberTypedT, OutputEx, Widen, C32, C64, F16,
+};
 
 pub trait CastNumber {
     fn cast_number(&self, number_type: NumberType) -> Self;
@@ -14,7 +17,18 @@ pub trait CastNumber {
 
 
 pub trait NumberEx:
-    ClNumber + NumberTypedT + NumCast + ToPrimitive + FromPrimitive + Zero + From<NumEx> + KernelArg
+    ClNumber
+    + NumberTypedT
+    + NumCast
+    + ToPrimitive
+    + FromPrimitive
+    + Zero
+    + From<NumEx>
+    + KernelArg
+    + LeBytes
+    + Elementwise
+    + Widen
+    + CastBounds
 {
 }
 
@@ -30,6 +44,9 @@ impl<'a> NumberEx for i64 {}
 impl<'a> NumberEx for f64 {}
 impl<'a> NumberEx for usize {}
 impl<'a> NumberEx for isize {}
+impl<'a> NumberEx for F16 {}
+impl<'a> NumberEx for C32 {}
+impl<'a> NumberEx for C64 {}
 
 // #[derive(NifRecord)]
 // #[tag = "global_work_size"]
@@ -49,6 +66,99 @@ pub enum NumEx {
     F64(f64),
     Usize(usize),
     Isize(isize),
+    F16(F16),
+    C32(C32),
+    C64(C64),
+}
+
+/// A number tagged with the type it should have, e.g. `{:i32, 10}`.
+#[derive(NifTuple, Debug, Clone, Copy)]
+pub struct TypedNumEx {
+    number_type: NumberType,
+    num: NumEx,
+}
+
+impl TypedNumEx {
+    /// The number cast to its tagged type. Errors if the type can't hold it.
+    pub fn num(&self) -> OutputEx<NumEx> {
+        self.num.exact_cast(self.number_type)
+    }
+}
+
+impl NumEx {
+    /// Casts the number to `T` the way `mode` says. Only `CastMode::Checked` can fail.
+    pub fn cast_to<T: NumberEx>(&self, mode: CastMode) -> Option<T> {
+        if T::number_type_of().is_complex() {
+            // complex numbers take any number.
+            return Some(<T as From<NumEx>>::from(*self));
+        }
+        match mode {
+            CastMode::Wrapping => Some(<T as From<NumEx>>::from(*self)),
+            CastMode::Checked => match self {
+                NumEx::C32(val) if val.im() != 0.0 => None,
+                NumEx::C64(val) if val.im() != 0.0 => None,
+                _ => self.real_cast(),
+            },
+            CastMode::Saturating => Some(self.real_cast().unwrap_or_else(|| {
+                let val = f64::from(self);
+                if val.is_nan() {
+                    T::zero()
+                } else if val < 0.0 {
+                    T::lowest()
+                } else {
+                    T::highest()
+                }
+            })),
+        }
+    }
+
+    /// Casts the number to `T` only if `T` holds it exactly: in range and, for an
+    /// integer `T`, without a fraction.
+    pub fn exact_cast_to<T: NumberEx>(&self) -> OutputEx<T> {
+        let target = T::number_type_of();
+        let drops_fraction =
+            !target.is_float() && !target.is_complex() && f64::from(self).fract() != 0.0;
+        match self.cast_to::<T>(CastMode::Checked) {
+            Some(val) if !drops_fraction => Ok(val),
+            _ => Err(NumberTypeError::DoesNotFit(format!("{:?}", self), target).into()),
+        }
+    }
+
+    /// Like `exact_cast_to` for a number type only known at runtime.
+    pub fn exact_cast(&self, number_type: NumberType) -> OutputEx<NumEx> {
+        apply_number_type!(number_type, _exact_cast, [self])
+    }
+
+    /// Casts the (real part of the) number with `NumCast`, which fails when it doesn't fit.
+    fn real_cast<T: NumberEx>(&self) -> Option<T> {
+        match *self {
+            NumEx::U8(val) => <T as NumCast>::from(val),
+            NumEx::I8(val) => <T as NumCast>::from(val),
+            NumEx::U16(val) => <T as NumCast>::from(val),
+            NumEx::I16(val) => <T as NumCast>::from(val),
+            NumEx::U32(val) => <T as NumCast>::from(val),
+            NumEx::I32(val) => <T as NumCast>::from(val),
+            NumEx::F32(val) => <T as NumCast>::from(val),
+            NumEx::U64(val) => <T as NumCast>::from(val),
+            NumEx::I64(val) => <T as NumCast>::from(val),
+            NumEx::F64(val) => <T as NumCast>::from(val),
+            NumEx::Usize(val) => <T as NumCast>::from(val),
+            NumEx::Isize(val) => <T as NumCast>::from(val),
+            NumEx::F16(val) => <T as NumCast>::from(val),
+            NumEx::C32(val) => <T as NumCast>::from(val.re()),
+            NumEx::C64(val) => <T as NumCast>::from(val.re()),
+        }
+    }
+}
+
+fn _exact_cast<T: NumberEx + Into<NumEx>>(num: &NumEx) -> OutputEx<NumEx> {
+    num.exact_cast_to::<T>().map(Into::into)
+}
+
+impl NumberTyped for TypedNumEx {
+    fn number_type(&self) -> NumberType {
+        self.number_type
+    }
 }
 
 impl CastNumber for NumEx {
@@ -67,6 +177,9 @@ impl CastNumber for NumEx {
             NT::F64 => NumEx::F64(self.into()),
             NT::Usize => NumEx::Usize(self.into()),
             NT::Isize => NumEx::Isize(self.into()),
+            NT::F16 => NumEx::F16(self.into()),
+            NT::C32 => NumEx::C32(self.into()),
+            NT::C64 => NumEx::C64(self.into()),
         }
     }
 }
@@ -86,6 +199,9 @@ unsafe impl KernelArg for NumEx {
             NumEx::F64(num) => num.as_kernel_arg(),
             NumEx::Usize(num) => num.as_kernel_arg(),
             NumEx::Isize(num) => num.as_kernel_arg(),
+            NumEx::F16(num) => num.as_kernel_arg(),
+            NumEx::C32(num) => num.as_kernel_arg(),
+            NumEx::C64(num) => num.as_kernel_arg(),
         }
     }
 }
@@ -107,6 +223,9 @@ macro_rules! impl_num_ex_primitive_conversion {
                     NumEx::F64(val) => val as $t,
                     NumEx::Usize(val) => val as $t,
                     NumEx::Isize(val) => val as $t,
+                    NumEx::F16(val) => f64::from(val) as $t,
+                    NumEx::C32(val) => val.re() as $t,
+                    NumEx::C64(val) => val.re() as $t,
                 }
             }
         }
@@ -126,6 +245,9 @@ macro_rules! impl_num_ex_primitive_conversion {
                     NumEx::F64(val) => *val as $t,
                     NumEx::Usize(val) => *val as $t,
                     NumEx::Isize(val) => *val as $t,
+                    NumEx::F16(val) => f64::from(*val) as $t,
+                    NumEx::C32(val) => val.re() as $t,
+                    NumEx::C64(val) => val.re() as $t,
                 }
             }
         }
@@ -157,6 +279,69 @@ impl_num_ex_primitive_conversion!(f64, F64);
 impl_num_ex_primitive_conversion!(usize, Usize);
 impl_num_ex_primitive_conversion!(isize, Isize);
 
+impl From<NumEx> for F16 {
+    fn from(num: NumEx) -> F16 {
+        F16::from(&num)
+    }
+}
+
+impl From<&NumEx> for F16 {
+    fn from(num: &NumEx) -> F16 {
+        match num {
+            NumEx::F16(val) => *val,
+            NumEx::F32(val) => F16::from_f32(*val),
+            other => F16::from_f64(f64::from(other)),
+        }
+    }
+}
+
+impl From<F16> for NumEx {
+    fn from(num: F16) -> NumEx {
+        NumEx::F16(num)
+    }
+}
+
+impl From<&F16> for NumEx {
+    fn from(num: &F16) -> NumEx {
+        NumEx::F16(*num)
+    }
+}
+
+macro_rules! impl_num_ex_complex_conversion {
+    ($t:ident, $float:ty) => {
+        impl From<NumEx> for $t {
+            fn from(num: NumEx) -> $t {
+                $t::from(&num)
+            }
+        }
+
+        impl From<&NumEx> for $t {
+            fn from(num: &NumEx) -> $t {
+                match num {
+                    NumEx::C32(val) => (*val).into(),
+                    NumEx::C64(val) => (*val).into(),
+                    other => $t::new(f64::from(other) as $float, 0.0),
+                }
+            }
+        }
+
+        impl From<$t> for NumEx {
+            fn from(num: $t) -> NumEx {
+                NumEx::$t(num)
+            }
+        }
+
+        impl From<&$t> for NumEx {
+            fn from(num: &$t) -> NumEx {
+                NumEx::$t(*num)
+            }
+        }
+    };
+}
+
+impl_num_ex_complex_conversion!(C32, f32);
+impl_num_ex_complex_conversion!(C64, f64);
+
 impl NumberTyped for NumEx {
     fn number_type(&self) -> NumberType {
         match self {
@@ -172,6 +357,9 @@ impl NumberTyped for NumEx {
             NumEx::F64(..) => NumberType::F64,
             NumEx::Usize(..) => NumberType::Usize,
             NumEx::Isize(..) => NumberType::Isize,
+            NumEx::F16(..) => NumberType::F16,
+            NumEx::C32(..) => NumberType::C32,
+            NumEx::C64(..) => NumberType::C64,
         }
     }
 }
@@ -347,6 +535,9 @@ impl_number_typed_for_vec!(i64, I64);
 impl_number_typed_for_vec!(f64, F64);
 impl_number_typed_for_vec!(usize, Usize);
 impl_number_typed_for_vec!(isize, Isize);
+impl_number_typed_for_vec!(F16, F16);
+impl_number_typed_for_vec!(C32, C32);
+impl_number_typed_for_vec!(C64, C64);
 
 // impl NumberTyped for NumberVector {
 //     fn number_type(&self) -> NumberType {
@@ -1,7 +1,10 @@
 
 use rustler::NifUntaggedEnum;
 
-use crate::{NumberEx, NumberType, NumberTyped, OutputEx, CastNumber, NumEx};
+use crate::{
+    CastMode, CastNumber, NumEx, NumberEx, NumberType, NumberTypeError, NumberTyped, OutputEx, C32,
+    C64, F16,
+};
 use NumberType as NT;
 
 #[derive(Debug)]
@@ -23,6 +26,32 @@ fn cast_vec_to_rt_list<T: NumberEx + From<NumEx>>(data: Vec<NumEx>) -> RuntimeNu
     RuntimeNumberList::from_vec(data.iter().map(|num| From::from(*num)).collect::<Vec<T>>())
 }
 
+fn _cast_to_type_with_mode<T: NumberEx + Into<NumEx>>(
+    rt_list: &RuntimeNumberList,
+    other_type: NumberType,
+    mode: CastMode,
+) -> OutputEx<RuntimeNumberList> {
+    let data: Vec<NumEx> = rt_list.force_as_slice::<T>().iter().map(|num| (*num).into()).collect();
+    apply_number_type!(other_type, cast_vec_to_rt_list_with_mode, [data, mode])
+}
+
+fn cast_vec_to_rt_list_with_mode<T: NumberEx>(
+    data: Vec<NumEx>,
+    mode: CastMode,
+) -> OutputEx<RuntimeNumberList> {
+    let mut casted: Vec<T> = Vec::with_capacity(data.len());
+    for (index, num) in data.iter().enumerate() {
+        match num.cast_to::<T>(mode) {
+            Some(casted_num) => casted.push(casted_num),
+            None => {
+                let e = NumberTypeError::CastFailed(index, format!("{:?}", num), T::number_type_of());
+                return Err(e.into());
+            }
+        }
+    }
+    Ok(RuntimeNumberList::from_vec(casted))
+}
+
 impl CastNumber for RuntimeNumberList {
     fn cast_number(&self, number_type: NumberType) -> RuntimeNumberList {
         apply_number_type!(self._number_type, _cast_to_type, [self, number_type])
@@ -34,6 +63,16 @@ impl RuntimeNumberList {
         self._len
     }
 
+    /// Casts the numbers to `number_type`; `CastMode::Checked` fails on the first
+    /// number that doesn't fit.
+    pub fn cast_with_mode(
+        &self,
+        number_type: NumberType,
+        mode: CastMode,
+    ) -> OutputEx<RuntimeNumberList> {
+        apply_number_type!(self._number_type, _cast_to_type_with_mode, [self, number_type, mode])
+    }
+
     pub fn is_empty(&self) -> bool {
         self._len == 0
     }
@@ -68,6 +107,14 @@ impl RuntimeNumberList {
         }
     }
 
+    pub fn resize<N: NumberEx>(&mut self, new_len: usize, fill: N) {
+        unsafe {
+            let mut data: Vec<N> = self.borrow_vec();
+            data.resize(new_len, fill);
+            self.update_from_borrowed(data);
+        }
+    }
+
     pub unsafe fn borrow_vec<N: NumberEx>(&self) -> Vec<N> {
         assert_eq!(N::number_type_of(), self._number_type);
         Vec::from_raw_parts(self._ptr as *mut N, self._len, self._capacity)
@@ -135,6 +182,12 @@ impl RuntimeNumberList {
         Ok(unsafe { self.unchecked_as_slice_mut() })
     }
 
+    /// The numbers' raw bytes in native byte order.
+    pub fn as_bytes(&self) -> &[u8] {
+        let byte_len = self._len * self._number_type.size_of();
+        unsafe { std::slice::from_raw_parts(self._ptr as *const u8, byte_len) }
+    }
+
     pub fn from_vec<N: NumberEx>(mut v: Vec<N>) -> RuntimeNumberList {
         let rt_list = RuntimeNumberList {
             _number_type: N::number_type_of(),
@@ -270,6 +323,9 @@ pub enum NumberListEx {
     F64(Vec::<f64>),
     Usize(Vec::<usize>),
     Isize(Vec::<isize>),
+    F16(Vec::<F16>),
+    C32(Vec::<C32>),
+    C64(Vec::<C64>),
 }
 
 impl NumberTyped for NumberListEx {
@@ -287,6 +343,9 @@ impl NumberTyped for NumberListEx {
             NumberListEx::F64(..) => NumberType::F64,
             NumberListEx::Usize(..) => NumberType::Usize,
             NumberListEx::Isize(..) => NumberType::Isize,
+            NumberListEx::F16(..) => NumberType::F16,
+            NumberListEx::C32(..) => NumberType::C32,
+            NumberListEx::C64(..) => NumberType::C64,
         }
     }
 }
@@ -308,6 +367,9 @@ impl From<NumberListEx> for RuntimeNumberList {
             NE::F64(data) => RuntimeNumberList::from_vec::<f64>(data),
             NE::Usize(data) => RuntimeNumberList::from_vec::<usize>(data),
             NE::Isize(data) => RuntimeNumberList::from_vec::<isize>(data),
+            NE::F16(data) => RuntimeNumberList::from_vec::<F16>(data),
+            NE::C32(data) => RuntimeNumberList::from_vec::<C32>(data),
+            NE::C64(data) => RuntimeNumberList::from_vec::<C64>(data),
         }
     }
 }
@@ -329,6 +391,9 @@ impl From<RuntimeNumberList> for NumberListEx {
                 NT::F64 => NE::F64(rt_nl.unchecked_to_vec()),
                 NT::Usize => NE::Usize(rt_nl.unchecked_to_vec()),
                 NT::Isize => NE::Isize(rt_nl.unchecked_to_vec()),
+                NT::F16 => NE::F16(rt_nl.unchecked_to_vec()),
+                NT::C32 => NE::C32(rt_nl.unchecked_to_vec()),
+                NT::C64 => NE::C64(rt_nl.unchecked_to_vec()),
             }
         }
     }
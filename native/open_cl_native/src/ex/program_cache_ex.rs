@@ -0,0 +1,96 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use opencl_core::ll::ffi::{self, cl_device_id};
+
+use crate::ex::program_ex::read_info_string;
+use crate::OutputEx;
+
+/// 64-bit FNV-1a. Used instead of `DefaultHasher` because cache file names must
+/// stay the same across Rust versions.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+fn device_info_string(device: cl_device_id, param: ffi::cl_uint) -> OutputEx<String> {
+    read_info_string(|size, value, size_ret| unsafe {
+        ffi::clGetDeviceInfo(device, param, size, value, size_ret)
+    })
+}
+
+/// Everything a cached program binary depends on.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ProgramCacheKey {
+    key: String,
+}
+
+impl ProgramCacheKey {
+    pub fn new(src: &str, options: &str, device: cl_device_id) -> OutputEx<ProgramCacheKey> {
+        let device_name = device_info_string(device, ffi::CL_DEVICE_NAME)?;
+        let device_version = device_info_string(device, ffi::CL_DEVICE_VERSION)?;
+        let driver_version = device_info_string(device, ffi::CL_DRIVER_VERSION)?;
+        let key = format!(
+            "source: {:016x}\noptions: {}\ndevice: {}\ndevice_version: {}\ndriver_version: {}\n",
+            fnv1a(src.as_bytes()),
+            options,
+            device_name,
+            device_version,
+            driver_version
+        );
+        Ok(ProgramCacheKey { key })
+    }
+
+    fn file_stem(&self) -> String {
+        format!("{:016x}", fnv1a(self.key.as_bytes()))
+    }
+}
+
+/// A directory of program binaries.
+///
+/// Each entry is a `<hash>.bin` binary plus a `<hash>.key` file holding the full
+/// key so that a hash collision or stale entry is treated as a miss.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ProgramCache {
+    dir: PathBuf,
+}
+
+impl ProgramCache {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> ProgramCache {
+        ProgramCache { dir: dir.into() }
+    }
+
+    fn path(&self, key: &ProgramCacheKey, ext: &str) -> PathBuf {
+        self.dir.join(format!("{}.{}", key.file_stem(), ext))
+    }
+
+    /// Returns the cached binary for `key`, if there is one.
+    pub fn load(&self, key: &ProgramCacheKey) -> Option<Vec<u8>> {
+        let stored_key = fs::read_to_string(self.path(key, "key")).ok()?;
+        if stored_key != key.key {
+            return None;
+        }
+        fs::read(self.path(key, "bin"))
+            .ok()
+            .filter(|binary| !binary.is_empty())
+    }
+
+    /// Stores `binary` for `key`, replacing any previous entry.
+    pub fn store(&self, key: &ProgramCacheKey, binary: &[u8]) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        // the key is written last so a reader never sees a key without its binary.
+        write_atomic(&self.path(key, "bin"), binary)?;
+        write_atomic(&self.path(key, "key"), key.key.as_bytes())
+    }
+}
+
+fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let tmp_path = path.with_extension(format!("tmp.{}", std::process::id()));
+    fs::write(&tmp_path, contents)?;
+    fs::rename(&tmp_path, path)
+}
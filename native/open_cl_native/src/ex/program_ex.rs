@@ -0,0 +1,465 @@
+use std::ffi::CString;
+use std::fmt;
+
+use opencl_core::ll::ffi::{self, cl_context, cl_device_id, cl_int, cl_program};
+use rustler::resource::ResourceArc;
+use rustler::{Encoder, NifMap, NifStruct, NifUnitEnum};
+
+use crate::ex::error_ex::{check_status, OnlyTrue};
+use crate::ex::kernel_ex::{BoundKernel, Kernel};
+use crate::ex::program_cache_ex::{ProgramCache, ProgramCacheKey};
+use crate::{BuildOptionsEx, KernelEx, OutputEx, SessionEx};
+
+/// The option every program is built with so that kernel arg info can be queried.
+const KERNEL_ARG_INFO_OPTION: &str = "-cl-kernel-arg-info";
+
+#[derive(Debug, Fail, PartialEq, Eq, Clone)]
+pub enum ProgramError {
+    #[fail(display = "Build options contain a NUL byte - {:?}", _0)]
+    InvalidOptions(String),
+}
+
+/// Reads a variable length info value by asking for its size first.
+pub fn read_info_bytes<F>(get_info: F) -> OutputEx<Vec<u8>>
+where
+    F: Fn(usize, *mut libc::c_void, *mut usize) -> cl_int,
+{
+    let mut size: usize = 0;
+    check_status(get_info(0, std::ptr::null_mut(), &mut size))?;
+    let mut bytes: Vec<u8> = vec![0; size];
+    check_status(get_info(
+        size,
+        bytes.as_mut_ptr() as *mut libc::c_void,
+        std::ptr::null_mut(),
+    ))?;
+    Ok(bytes)
+}
+
+/// Reads a null-terminated info string.
+pub fn read_info_string<F>(get_info: F) -> OutputEx<String>
+where
+    F: Fn(usize, *mut libc::c_void, *mut usize) -> cl_int,
+{
+    let mut bytes = read_info_bytes(get_info)?;
+    while bytes.last() == Some(&0) {
+        bytes.pop();
+    }
+    Ok(String::from_utf8_lossy(&bytes[..]).into_owned())
+}
+
+#[derive(NifUnitEnum, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SeverityEx {
+    Error,
+    Warning,
+    Note,
+}
+
+/// A single `<source>:line:column: severity: message` line of a build log.
+#[derive(NifMap, Debug, PartialEq, Eq, Clone)]
+pub struct DiagnosticEx {
+    line: usize,
+    column: usize,
+    severity: SeverityEx,
+    message: String,
+}
+
+impl DiagnosticEx {
+    fn parse(log_line: &str) -> Option<DiagnosticEx> {
+        // e.g. "<kernel>:3:5: error: use of undeclared identifier 'x'"
+        let mut parts = log_line.splitn(5, ':');
+        let _source = parts.next()?;
+        let line = parts.next()?.trim().parse().ok()?;
+        let column = parts.next()?.trim().parse().ok()?;
+        let severity = match parts.next()?.trim() {
+            "error" | "fatal error" => SeverityEx::Error,
+            "warning" => SeverityEx::Warning,
+            "note" => SeverityEx::Note,
+            _ => return None,
+        };
+        let message = parts.next().unwrap_or("").trim().to_owned();
+        Some(DiagnosticEx {
+            line,
+            column,
+            severity,
+            message,
+        })
+    }
+
+    pub fn parse_log(log: &str) -> Vec<DiagnosticEx> {
+        log.lines().filter_map(DiagnosticEx::parse).collect()
+    }
+}
+
+#[derive(NifMap, Debug, PartialEq, Eq, Clone)]
+pub struct DeviceBuildLogEx {
+    device_name: String,
+    log: String,
+    diagnostics: Vec<DiagnosticEx>,
+}
+
+impl DeviceBuildLogEx {
+    fn read(program: cl_program, device: cl_device_id) -> OutputEx<DeviceBuildLogEx> {
+        let device_name = read_info_string(|size, value, size_ret| unsafe {
+            ffi::clGetDeviceInfo(device, ffi::CL_DEVICE_NAME, size, value, size_ret)
+        })?;
+        let log = read_info_string(|size, value, size_ret| unsafe {
+            ffi::clGetProgramBuildInfo(
+                program,
+                device,
+                ffi::CL_PROGRAM_BUILD_LOG,
+                size,
+                value,
+                size_ret,
+            )
+        })?;
+        let diagnostics = DiagnosticEx::parse_log(&log[..]);
+        Ok(DeviceBuildLogEx {
+            device_name,
+            log,
+            diagnostics,
+        })
+    }
+}
+
+#[derive(NifStruct, Debug, PartialEq, Eq, Clone)]
+#[must_use]
+#[module = "OpenCL.BuildError"]
+pub struct BuildErrorEx {
+    status_code: i32,
+    options: String,
+    logs: Vec<DeviceBuildLogEx>,
+    __exception__: OnlyTrue,
+}
+
+impl BuildErrorEx {
+    /// Collects the build log of every device of a program that failed to build.
+    pub fn from_program(
+        program: cl_program,
+        devices: &[cl_device_id],
+        options: &str,
+        status_code: cl_int,
+    ) -> BuildErrorEx {
+        let logs = devices
+            .iter()
+            .filter_map(|device| DeviceBuildLogEx::read(program, *device).ok())
+            .collect();
+        BuildErrorEx {
+            status_code,
+            options: options.to_owned(),
+            logs,
+            __exception__: OnlyTrue::True,
+        }
+    }
+}
+
+/// An owned raw program built for a single device.
+///
+/// The source is kept so a program loaded from a binary can be rebuilt when the
+/// binary lacks kernel arg info. The program and its (retained) context are
+/// released on drop.
+pub struct Program {
+    ptr: cl_program,
+    context: cl_context,
+    device: cl_device_id,
+    src: String,
+    options: String,
+}
+
+unsafe impl Send for Program {}
+unsafe impl Sync for Program {}
+
+impl Program {
+    /// Compiles `src` for `device` in `context`.
+    ///
+    /// With a `cache` the program binary is loaded from (or stored to) disk instead;
+    /// a missing, stale or unloadable binary, or one without kernel arg info, falls
+    /// back to compiling `src`.
+    pub fn create(
+        context: cl_context,
+        device: cl_device_id,
+        src: &str,
+        build_options: &BuildOptionsEx,
+        cache: Option<&ProgramCache>,
+    ) -> OutputEx<Program> {
+        let options = format!(
+            "{} {}",
+            KERNEL_ARG_INFO_OPTION,
+            build_options.to_options_string()
+        );
+        let options = options.trim_end();
+        let cache = match cache {
+            Some(cache) => cache,
+            None => return Program::create_with_source(context, device, src, options),
+        };
+        let key = ProgramCacheKey::new(src, options, device)?;
+        if let Some(binary) = cache.load(&key) {
+            match Program::create_with_binary(context, device, src, &binary[..], options) {
+                Ok(program) if program.has_arg_info() => return Ok(program),
+                _ => {}
+            }
+        }
+        let program = Program::create_with_source(context, device, src, options)?;
+        if let Ok(binary) = program.binary() {
+            // failing to write the cache only costs a recompile next time.
+            let _ = cache.store(&key, &binary[..]);
+        }
+        Ok(program)
+    }
+
+    /// Builds this program again for `device` in `context` from its binary, or from
+    /// its source if the binary can't be loaded or lacks kernel arg info.
+    pub fn create_copy(&self, context: cl_context, device: cl_device_id) -> OutputEx<Program> {
+        let (src, options) = (&self.src[..], &self.options[..]);
+        let binary = self.binary()?;
+        match Program::create_with_binary(context, device, src, &binary[..], options) {
+            Ok(program) if program.has_arg_info() => Ok(program),
+            _ => Program::create_with_source(context, device, src, options),
+        }
+    }
+
+    fn create_with_source(
+        context: cl_context,
+        device: cl_device_id,
+        src: &str,
+        options: &str,
+    ) -> OutputEx<Program> {
+        let src_ptr = src.as_ptr() as *const libc::c_char;
+        let src_len = src.len();
+        let mut status: cl_int = 0;
+        let ptr =
+            unsafe { ffi::clCreateProgramWithSource(context, 1, &src_ptr, &src_len, &mut status) };
+        check_status(status)?;
+        let program = unsafe { Program::new(ptr, context, device, src, options) };
+        program.build()?;
+        Ok(program)
+    }
+
+    fn create_with_binary(
+        context: cl_context,
+        device: cl_device_id,
+        src: &str,
+        binary: &[u8],
+        options: &str,
+    ) -> OutputEx<Program> {
+        let binary_ptr = binary.as_ptr();
+        let binary_len = binary.len();
+        let mut binary_status: cl_int = 0;
+        let mut status: cl_int = 0;
+        let ptr = unsafe {
+            ffi::clCreateProgramWithBinary(
+                context,
+                1,
+                &device,
+                &binary_len,
+                &binary_ptr,
+                &mut binary_status,
+                &mut status,
+            )
+        };
+        check_status(status)?;
+        let program = unsafe { Program::new(ptr, context, device, src, options) };
+        check_status(binary_status)?;
+        program.build()?;
+        Ok(program)
+    }
+
+    /// Takes ownership of `ptr` and retains `context`.
+    unsafe fn new(
+        ptr: cl_program,
+        context: cl_context,
+        device: cl_device_id,
+        src: &str,
+        options: &str,
+    ) -> Program {
+        ffi::clRetainContext(context);
+        Program {
+            ptr,
+            context,
+            device,
+            src: src.to_owned(),
+            options: options.to_owned(),
+        }
+    }
+
+    /// Whether the arg info of every kernel can be queried.
+    ///
+    /// OpenCL only guarantees arg info for programs built from source, and it is
+    /// needed by `Kernel.arg_info` and to type untagged number args.
+    fn has_arg_info(&self) -> bool {
+        let names = match self.kernel_names() {
+            Ok(names) => names,
+            Err(_) => return false,
+        };
+        names.iter().all(|name| {
+            let has_info = Kernel::create_from_program(self.ptr, name).and_then(|kernel| {
+                match kernel.num_args()? {
+                    0 => Ok(()),
+                    _ => kernel.arg_info(0).map(|_| ()),
+                }
+            });
+            has_info.is_ok()
+        })
+    }
+
+    /// The options that were asked for, without the arg info option.
+    fn requested_options(&self) -> &str {
+        self.options[KERNEL_ARG_INFO_OPTION.len()..].trim_start()
+    }
+
+    fn build(&self) -> OutputEx<()> {
+        let c_options = CString::new(&self.options[..])
+            .map_err(|_| ProgramError::InvalidOptions(self.requested_options().to_owned()))?;
+        let status = unsafe {
+            ffi::clBuildProgram(
+                self.ptr,
+                1,
+                &self.device,
+                c_options.as_ptr(),
+                None,
+                std::ptr::null_mut(),
+            )
+        };
+        if status == ffi::CL_BUILD_PROGRAM_FAILURE as cl_int {
+            let devices = [self.device];
+            let options = self.requested_options();
+            let err = BuildErrorEx::from_program(self.ptr, &devices, options, status);
+            return Err(err.into());
+        }
+        check_status(status)
+    }
+
+    /// The compiled binary of the program for its device.
+    pub fn binary(&self) -> OutputEx<Vec<u8>> {
+        let mut size: usize = 0;
+        check_status(unsafe {
+            ffi::clGetProgramInfo(
+                self.ptr,
+                ffi::CL_PROGRAM_BINARY_SIZES,
+                std::mem::size_of::<usize>(),
+                &mut size as *mut usize as *mut libc::c_void,
+                std::ptr::null_mut(),
+            )
+        })?;
+        let mut binary: Vec<u8> = vec![0; size];
+        let mut binary_ptr = binary.as_mut_ptr();
+        check_status(unsafe {
+            ffi::clGetProgramInfo(
+                self.ptr,
+                ffi::CL_PROGRAM_BINARIES,
+                std::mem::size_of::<*mut u8>(),
+                &mut binary_ptr as *mut *mut u8 as *mut libc::c_void,
+                std::ptr::null_mut(),
+            )
+        })?;
+        Ok(binary)
+    }
+
+    pub fn ptr(&self) -> cl_program {
+        self.ptr
+    }
+
+    pub fn context_ptr(&self) -> cl_context {
+        self.context
+    }
+
+    pub fn kernel_names(&self) -> OutputEx<Vec<String>> {
+        let names = read_info_string(|size, value, size_ret| unsafe {
+            ffi::clGetProgramInfo(
+                self.ptr,
+                ffi::CL_PROGRAM_KERNEL_NAMES,
+                size,
+                value,
+                size_ret,
+            )
+        })?;
+        Ok(names
+            .split(';')
+            .filter(|name| !name.is_empty())
+            .map(|name| name.to_owned())
+            .collect())
+    }
+}
+
+impl Drop for Program {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::clReleaseProgram(self.ptr);
+            ffi::clReleaseContext(self.context);
+        }
+    }
+}
+
+impl fmt::Debug for Program {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Program {{ ptr: {:?} }}", self.ptr)
+    }
+}
+
+#[derive(NifStruct)]
+#[must_use]
+#[module = "OpenCL.Program"]
+pub struct ProgramEx {
+    __native__: ResourceArc<Program>,
+}
+
+impl Clone for ProgramEx {
+    fn clone(&self) -> ProgramEx {
+        ProgramEx {
+            __native__: self.__native__.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for ProgramEx {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ProgramEx {{ native: {:?} }}", *self.__native__)
+    }
+}
+
+impl From<Program> for ProgramEx {
+    fn from(program: Program) -> ProgramEx {
+        ProgramEx {
+            __native__: ResourceArc::new(program),
+        }
+    }
+}
+
+impl ProgramEx {
+    pub fn program(&self) -> &Program {
+        &self.__native__
+    }
+
+    pub fn create_kernel(&self, name: &str) -> OutputEx<KernelEx> {
+        let kernel = Kernel::create_from_program(self.program().ptr(), name)?;
+        let bound = BoundKernel::new(kernel, self.clone())?;
+        Ok(KernelEx::from(bound))
+    }
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn program_create(
+    session: SessionEx,
+    src: String,
+    build_options: BuildOptionsEx,
+    cache_dir: Option<String>,
+) -> OutputEx<ProgramEx> {
+    let cache = cache_dir.map(ProgramCache::new);
+    Program::create(
+        session.context_ptr(),
+        session.device_ptr(),
+        &src[..],
+        &build_options,
+        cache.as_ref(),
+    )
+    .map(ProgramEx::from)
+}
+
+#[rustler::nif]
+pub fn program_self_kernel_names(program: ProgramEx) -> OutputEx<Vec<String>> {
+    program.program().kernel_names()
+}
+
+#[rustler::nif]
+pub fn program_self_create_kernel(program: ProgramEx, name: String) -> OutputEx<KernelEx> {
+    program.create_kernel(&name[..])
+}
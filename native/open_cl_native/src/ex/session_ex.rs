@@ -1,29 +1,47 @@
 use std::fmt;
 
-use opencl_core::ll::utils;
-use opencl_core::{
-    Buffer, CommandQueueOptions, CommandQueueProperties, Device, MemConfig, Session,
+use opencl_core::ll::ffi::{
+    self, cl_command_queue, cl_context, cl_device_id, cl_event, cl_int, cl_program,
 };
+use opencl_core::ll::utils;
+use opencl_core::ll::{CommandQueuePtr, ContextPtr, DevicePtr};
+use opencl_core::{CommandQueueProperties, Device, MemConfig, Session};
 // use opencl_core::ll::{DevicePtr};
 use rustler::resource::ResourceArc;
-use rustler::{Encoder, NifStruct};
+use rustler::types::Pid;
+use rustler::{Encoder, Env, NifStruct, Term};
 
-use super::{OutputEx, WrapperEx, WrapperExResource};
+use super::OutputEx;
 
+use crate::atoms;
+use crate::ex::error_ex::check_status;
+use crate::ex::event_ex::{encode_status_result, ProfilingInfoEx};
+use crate::ex::program_cache_ex::ProgramCache;
 use crate::traits::NativeWrapper;
 
 use crate::{
-    ArrayEx, BufferCreatorEx, BufferEx, CommandQueueOptionsEx, CommandQueuePropEx, DeviceEx,
-    KernelOpEx, MemConfigEx, NumberEx, NumberType, NumberTyped, RuntimeNumberList, NumberListEx,
+    Array, ArrayError, ArrayEx, BoundsError, BufferCreatorEx, BufferEx, BufferMap, BufferMapEx,
+    BuildOptionsEx, CommandQueueOptionsEx, CommandQueuePropEx, DeviceEx, DimsEx, Event, EventEx,
+    KernelEx, KernelOpEx, MapAccessEx, MemConfigEx, NumEx, NumberEx, NumberListEx, NumberType,
+    NumberTyped, PitchesEx, Program, ProgramEx, RuntimeNumberList, WorkEx,
 };
 
-impl WrapperExResource for Session {}
+/// A trivial source for the `Session`s of opencl_core, which always compile a program.
+///
+/// Sessions run kernels from their own `Program` instead, built with the build options.
+const PLACEHOLDER_SRC: &str = "__kernel void open_cl_session_placeholder() {}";
+
+/// A session and the program its kernels are created from.
+pub struct SessionWrapper {
+    session: Session,
+    program: ProgramEx,
+}
 
 #[derive(NifStruct)]
 #[must_use]
 #[module = "OpenCL.Session"]
 pub struct SessionEx {
-    __native__: ResourceArc<WrapperEx<Session>>,
+    __native__: ResourceArc<SessionWrapper>,
     _unconstructable: (),
 }
 
@@ -35,7 +53,7 @@ impl fmt::Debug for SessionEx {
 
 impl NativeWrapper<Session> for SessionEx {
     fn native(&self) -> &Session {
-        &self.__native__.item
+        &self.__native__.session
     }
 }
 
@@ -47,48 +65,123 @@ fn parse_command_queue_props(props: Vec<CommandQueuePropEx>) -> Option<CommandQu
     }
 }
 
-fn wrap_sessions(sessions: Vec<Session>) -> Vec<SessionEx> {
+fn wrap_sessions(
+    sessions: Vec<Session>,
+    src: &str,
+    build_options: &BuildOptionsEx,
+    cache_dir: Option<String>,
+) -> OutputEx<Vec<SessionEx>> {
+    let cache = cache_dir.map(ProgramCache::new);
     sessions
         .into_iter()
-        .map(|sess| SessionEx::new(sess))
+        .map(|sess| {
+            let (context, device) = session_ptrs(&sess);
+            let program = Program::create(context, device, src, build_options, cache.as_ref())?;
+            Ok(SessionEx::new(sess, ProgramEx::from(program)))
+        })
         .collect()
 }
 
+fn session_ptrs(session: &Session) -> (cl_context, cl_device_id) {
+    unsafe {
+        (
+            session.context().low_level_context().context_ptr(),
+            session.device().low_level_device().device_ptr(),
+        )
+    }
+}
+
 impl SessionEx {
-    pub fn new(session: Session) -> SessionEx {
+    pub fn new(session: Session, program: ProgramEx) -> SessionEx {
         SessionEx {
-            __native__: session.into_resource_arc(),
+            __native__: ResourceArc::new(SessionWrapper { session, program }),
             _unconstructable: (),
         }
     }
 
-    pub fn create(src: &str, props: Vec<CommandQueuePropEx>) -> OutputEx<Vec<SessionEx>> {
+    pub fn create(
+        src: &str,
+        props: Vec<CommandQueuePropEx>,
+        build_options: BuildOptionsEx,
+        cache_dir: Option<String>,
+    ) -> OutputEx<Vec<SessionEx>> {
         let cq_props = parse_command_queue_props(props);
-        let hl_sessions: Vec<Session> = Session::create(src, cq_props)?;
-        Ok(wrap_sessions(hl_sessions))
+        let hl_sessions: Vec<Session> = Session::create(PLACEHOLDER_SRC, cq_props)?;
+        wrap_sessions(hl_sessions, src, &build_options, cache_dir)
     }
 
     pub fn create_with_devices(
         src: &str,
         devices: &[DeviceEx],
         props: Vec<CommandQueuePropEx>,
+        build_options: BuildOptionsEx,
+        cache_dir: Option<String>,
     ) -> OutputEx<Vec<SessionEx>> {
         let cq_props = parse_command_queue_props(props);
         let hl_devices: Vec<Device> = devices.iter().map(|d| d.native().clone()).collect();
-        let hl_sessions: Vec<Session> = Session::create_with_devices(hl_devices, src, cq_props)?;
-        Ok(wrap_sessions(hl_sessions))
+        let hl_sessions: Vec<Session> =
+            Session::create_with_devices(hl_devices, PLACEHOLDER_SRC, cq_props)?;
+        wrap_sessions(hl_sessions, src, &build_options, cache_dir)
+    }
+
+    /// A session with its own command queue that shares the program.
+    ///
+    /// The program is rebuilt from its binary if the copy got a new context.
+    pub fn create_copy(&self) -> OutputEx<SessionEx> {
+        let session = self.native().create_copy()?;
+        let (context, device) = session_ptrs(&session);
+        let program = self.program().program();
+        if program.context_ptr() == context {
+            return Ok(SessionEx::new(session, self.program().clone()));
+        }
+        let program = program.create_copy(context, device)?;
+        Ok(SessionEx::new(session, ProgramEx::from(program)))
+    }
+
+    pub fn program(&self) -> &ProgramEx {
+        &self.__native__.program
     }
 
     pub fn device(&self) -> DeviceEx {
         DeviceEx::new(self.native().device().clone())
     }
 
+    /// Runs `f` with the session's raw command queue while holding the queue lock.
+    pub fn with_queue<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(cl_command_queue) -> R,
+    {
+        let queue = self.native().read_queue();
+        let queue_ptr = unsafe { queue.low_level_queue().command_queue_ptr() };
+        f(queue_ptr)
+    }
+
+    /// Submits every command enqueued so far to the device.
+    ///
+    /// Needed after an enqueue whose event is not waited on; otherwise the command
+    /// may never be submitted and its status and callbacks never change.
+    pub fn flush(&self) -> OutputEx<()> {
+        check_status(self.with_queue(|queue| unsafe { ffi::clFlush(queue) }))
+    }
+
+    pub fn program_ptr(&self) -> cl_program {
+        self.program().program().ptr()
+    }
+
+    pub fn context_ptr(&self) -> cl_context {
+        session_ptrs(self.native()).0
+    }
+
+    pub fn device_ptr(&self) -> cl_device_id {
+        session_ptrs(self.native()).1
+    }
+
     pub fn clone_native(&self) -> Session {
         self.native().clone()
     }
 
     pub fn native(&self) -> &Session {
-        &self.__native__.item
+        &self.__native__.session
     }
 }
 
@@ -99,8 +192,13 @@ impl From<SessionEx> for Session {
 }
 
 #[rustler::nif]
-fn session_create(src: String, props: Vec<CommandQueuePropEx>) -> OutputEx<Vec<SessionEx>> {
-    SessionEx::create(&src[..], props)
+fn session_create(
+    src: String,
+    props: Vec<CommandQueuePropEx>,
+    build_options: BuildOptionsEx,
+    cache_dir: Option<String>,
+) -> OutputEx<Vec<SessionEx>> {
+    SessionEx::create(&src[..], props, build_options, cache_dir)
 }
 
 #[rustler::nif]
@@ -108,8 +206,10 @@ fn session_create_with_devices(
     src: String,
     devices: Vec<DeviceEx>,
     props: Vec<CommandQueuePropEx>,
+    build_options: BuildOptionsEx,
+    cache_dir: Option<String>,
 ) -> OutputEx<Vec<SessionEx>> {
-    SessionEx::create_with_devices(&src[..], &devices[..], props)
+    SessionEx::create_with_devices(&src[..], &devices[..], props, build_options, cache_dir)
 }
 
 #[rustler::nif]
@@ -203,6 +303,104 @@ fn _create_buffer_from_array<T: NumberEx>(
     // }
 }
 
+fn cl_bool(b: bool) -> ffi::cl_bool {
+    if b {
+        ffi::CL_TRUE
+    } else {
+        ffi::CL_FALSE
+    }
+}
+
+/// Enqueues a write of `data` into `buffer` starting at element `offset`.
+///
+/// Works on raw `cl_mem`s so that sub-buffers can be written too.
+fn enqueue_write<T: NumberEx>(
+    sess: &SessionEx,
+    buffer: &BufferEx,
+    offset: usize,
+    data: &[T],
+    blocking: bool,
+) -> OutputEx<Event> {
+    BoundsError::check_range(offset, data.len(), buffer.wrapper().len()?)?;
+    let mut event_ptr: cl_event = std::ptr::null_mut();
+    let status = sess.with_queue(|queue| unsafe {
+        ffi::clEnqueueWriteBuffer(
+            queue,
+            buffer.wrapper().mem_ptr(),
+            cl_bool(blocking),
+            offset * std::mem::size_of::<T>(),
+            data.len() * std::mem::size_of::<T>(),
+            data.as_ptr() as *const libc::c_void,
+            0,
+            std::ptr::null(),
+            &mut event_ptr,
+        )
+    });
+    check_status(status)?;
+    Ok(unsafe { Event::new(event_ptr, vec![buffer.clone()]) })
+}
+
+/// Enqueues a read of `data.len()` elements of `buffer` starting at element `offset`.
+fn enqueue_read<T: NumberEx>(
+    sess: &SessionEx,
+    buffer: &BufferEx,
+    offset: usize,
+    data: &mut [T],
+    blocking: bool,
+) -> OutputEx<Event> {
+    BoundsError::check_range(offset, data.len(), buffer.wrapper().len()?)?;
+    let mut event_ptr: cl_event = std::ptr::null_mut();
+    let status = sess.with_queue(|queue| unsafe {
+        ffi::clEnqueueReadBuffer(
+            queue,
+            buffer.wrapper().mem_ptr(),
+            cl_bool(blocking),
+            offset * std::mem::size_of::<T>(),
+            data.len() * std::mem::size_of::<T>(),
+            data.as_mut_ptr() as *mut libc::c_void,
+            0,
+            std::ptr::null(),
+            &mut event_ptr,
+        )
+    });
+    check_status(status)?;
+    Ok(unsafe { Event::new(event_ptr, vec![buffer.clone()]) })
+}
+
+/// Blocks reading the `len` numbers of `buffer` starting at number `offset` into a new array.
+///
+/// The range is checked before anything is allocated, and an empty range is returned
+/// without enqueuing since OpenCL rejects zero-sized reads.
+fn read_range<T: NumberEx>(
+    sess: &SessionEx,
+    buffer: &BufferEx,
+    offset: usize,
+    len: usize,
+) -> OutputEx<ArrayEx> {
+    BoundsError::check_range(offset, len, buffer.wrapper().len()?)?;
+    if len == 0 {
+        return Ok(ArrayEx::from(RuntimeNumberList::from_vec(Vec::<T>::new())));
+    }
+    let mut data = utils::vec_filled_with::<T>(T::zero(), len);
+    enqueue_read(sess, buffer, offset, &mut data[..], true)?.wait()?;
+    Ok(ArrayEx::from(RuntimeNumberList::from_vec(data)))
+}
+
+/// Blocks writing `data` into `buffer` starting at number `offset`.
+///
+/// Like `read_range`, an empty write is only bounds checked and not enqueued.
+fn write_range<T: NumberEx>(
+    sess: &SessionEx,
+    buffer: &BufferEx,
+    offset: usize,
+    data: &[T],
+) -> OutputEx<()> {
+    if data.is_empty() {
+        return BoundsError::check_range(offset, 0, buffer.wrapper().len()?);
+    }
+    enqueue_write(sess, buffer, offset, data, true)?.wait()
+}
+
 fn _sync_write_buffer<T: NumberEx>(
     sess: &SessionEx,
     buffer: BufferEx,
@@ -211,11 +409,8 @@ fn _sync_write_buffer<T: NumberEx>(
 ) -> OutputEx<()> {
     let rt_list = array.read_lock();
     let data: &[T] = rt_list.force_as_slice();
-    let buffer_t: &Buffer<T> = buffer.wrapper().buffer().unwrap();
-    let cl_cq_opts: Option<CommandQueueOptions> = cq_options.map(|o| o.into());
-    sess.native()
-        .sync_write_buffer::<T, &[T]>(buffer_t, data, cl_cq_opts)
-        .map_err(From::from)
+    let offset = CommandQueueOptionsEx::offset_or_zero(&cq_options);
+    write_range(sess, &buffer, offset, data)
 }
 
 #[rustler::nif(schedule = "DirtyCpu")]
@@ -241,14 +436,9 @@ pub fn _sync_read_buffer<T: NumberEx>(
     buffer: BufferEx,
     cq_opts_ex: Option<CommandQueueOptionsEx>,
 ) -> OutputEx<ArrayEx> {
-    let buffer_t: &Buffer<T> = buffer.wrapper().buffer().unwrap();
-    let data = utils::vec_filled_with::<T>(T::zero(), buffer_t.len());
-    let cq_opts_cl: Option<CommandQueueOptions> = cq_opts_ex.map(|o| o.into());
-
-    sess.native()
-        .sync_read_buffer(buffer_t, data, cq_opts_cl)
-        .map_err(From::from)
-        .map(|num_vec| ArrayEx::from(RuntimeNumberList::from_vec(num_vec.unwrap())))
+    let offset = CommandQueueOptionsEx::offset_or_zero(&cq_opts_ex);
+    let len = buffer.wrapper().len()?.saturating_sub(offset);
+    read_range::<T>(sess, &buffer, offset, len)
 }
 
 #[rustler::nif(schedule = "DirtyCpu")]
@@ -261,15 +451,506 @@ pub fn session_self_read_buffer(
     apply_number_type!(num_type, _sync_read_buffer, [&session, buffer, cq_opts_ex])
 }
 
-pub fn _execute_sync_kernel_operation<T: NumberEx>(
-    session: &SessionEx,
-    kernel_op_ex: KernelOpEx,
+fn _read_buffer_into<T: NumberEx>(
+    sess: &SessionEx,
+    buffer: BufferEx,
+    array: ArrayEx,
 ) -> OutputEx<()> {
-    let kernel_op_cl = kernel_op_ex.into_kernel_operation::<T>()?;
-    let _ = session
-        .native()
-        .execute_sync_kernel_operation::<T>(kernel_op_cl)?;
-    Ok(())
+    let len = buffer.wrapper().len()?;
+    let mut rt_list = array.write_lock()?;
+    if rt_list.len() != len {
+        rt_list.resize::<T>(len, T::zero());
+        array.set_shape(None);
+    }
+    let data = rt_list.force_as_slice_mut::<T>();
+    enqueue_read(sess, &buffer, 0, data, true)?.wait()
+}
+
+/// Reads all of `buffer` into the existing `array`.
+///
+/// The array is resized (and its shape dropped) if its length differs from the buffer's,
+/// so reading into the same array repeatedly only allocates the first time.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn session_self_read_buffer_into(
+    session: SessionEx,
+    buffer: BufferEx,
+    array: ArrayEx,
+) -> OutputEx<()> {
+    let num_type = buffer.number_type();
+    num_type.type_check(array.number_type())?;
+    apply_number_type!(num_type, _read_buffer_into, [&session, buffer, array])
+}
+
+fn _read_buffer_range<T: NumberEx>(
+    sess: &SessionEx,
+    buffer: BufferEx,
+    offset: usize,
+    len: usize,
+) -> OutputEx<ArrayEx> {
+    read_range::<T>(sess, &buffer, offset, len)
+}
+
+/// Reads the `len` numbers of `buffer` starting at number `offset`.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn session_self_read_buffer_range(
+    session: SessionEx,
+    buffer: BufferEx,
+    offset: usize,
+    len: usize,
+) -> OutputEx<ArrayEx> {
+    let num_type = buffer.number_type();
+    apply_number_type!(
+        num_type,
+        _read_buffer_range,
+        [&session, buffer, offset, len]
+    )
+}
+
+fn _write_buffer_range<T: NumberEx>(
+    sess: &SessionEx,
+    buffer: BufferEx,
+    offset: usize,
+    array: ArrayEx,
+) -> OutputEx<()> {
+    let rt_list = array.read_lock();
+    let data: &[T] = rt_list.force_as_slice();
+    write_range(sess, &buffer, offset, data)
+}
+
+/// Writes all of `array` into `buffer` starting at number `offset`.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn session_self_write_array_to_buffer_range(
+    session: SessionEx,
+    buffer: BufferEx,
+    offset: usize,
+    array: ArrayEx,
+) -> OutputEx<()> {
+    let num_type = buffer.number_type();
+    num_type.type_check(array.number_type())?;
+    apply_number_type!(
+        num_type,
+        _write_buffer_range,
+        [&session, buffer, offset, array]
+    )
+}
+
+fn _copy_buffer<T: NumberEx>(
+    sess: &SessionEx,
+    src: BufferEx,
+    dst: BufferEx,
+    src_offset: usize,
+    dst_offset: usize,
+    len: usize,
+) -> OutputEx<()> {
+    dst.wrapper().type_check::<T>()?;
+    BoundsError::check_range(src_offset, len, src.wrapper().len()?)?;
+    BoundsError::check_range(dst_offset, len, dst.wrapper().len()?)?;
+    if len == 0 {
+        return Ok(());
+    }
+    let size_of_t = std::mem::size_of::<T>();
+    let mut event_ptr: cl_event = std::ptr::null_mut();
+    let status = sess.with_queue(|queue| unsafe {
+        ffi::clEnqueueCopyBuffer(
+            queue,
+            src.wrapper().mem_ptr(),
+            dst.wrapper().mem_ptr(),
+            src_offset * size_of_t,
+            dst_offset * size_of_t,
+            len * size_of_t,
+            0,
+            std::ptr::null(),
+            &mut event_ptr,
+        )
+    });
+    check_status(status)?;
+    unsafe { Event::new(event_ptr, vec![src.clone(), dst.clone()]) }.wait()
+}
+
+/// Copies `len` numbers from `src` at `src_offset` to `dst` at `dst_offset` on the device.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn session_self_copy_buffer(
+    session: SessionEx,
+    src: BufferEx,
+    dst: BufferEx,
+    src_offset: usize,
+    dst_offset: usize,
+    len: usize,
+) -> OutputEx<()> {
+    let num_type = src.number_type();
+    apply_number_type!(
+        num_type,
+        _copy_buffer,
+        [&session, src, dst, src_offset, dst_offset, len]
+    )
+}
+
+fn _fill_buffer<T: NumberEx>(
+    sess: &SessionEx,
+    buffer: BufferEx,
+    number: NumEx,
+    offset: usize,
+    len: usize,
+) -> OutputEx<()> {
+    let pattern: T = number.exact_cast_to()?;
+    BoundsError::check_range(offset, len, buffer.wrapper().len()?)?;
+    if len == 0 {
+        return Ok(());
+    }
+    let size_of_t = std::mem::size_of::<T>();
+    let mut event_ptr: cl_event = std::ptr::null_mut();
+    let status = sess.with_queue(|queue| unsafe {
+        ffi::clEnqueueFillBuffer(
+            queue,
+            buffer.wrapper().mem_ptr(),
+            &pattern as *const T as *const libc::c_void,
+            size_of_t,
+            offset * size_of_t,
+            len * size_of_t,
+            0,
+            std::ptr::null(),
+            &mut event_ptr,
+        )
+    });
+    check_status(status)?;
+    unsafe { Event::new(event_ptr, vec![buffer.clone()]) }.wait()
+}
+
+/// Sets `len` numbers of `buffer` starting at `offset` to `number`, which must fit in
+/// the buffer's number type.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn session_self_fill_buffer(
+    session: SessionEx,
+    buffer: BufferEx,
+    number: NumEx,
+    offset: usize,
+    len: usize,
+) -> OutputEx<()> {
+    let num_type = buffer.number_type();
+    apply_number_type!(
+        num_type,
+        _fill_buffer,
+        [&session, buffer, number, offset, len]
+    )
+}
+
+/// Maps the `len` numbers of `buffer` starting at `offset` into host memory.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn session_self_map_buffer(
+    session: SessionEx,
+    buffer: BufferEx,
+    access: MapAccessEx,
+    offset: usize,
+    len: usize,
+) -> OutputEx<BufferMapEx> {
+    BufferMap::map(&session, buffer, access, offset, len).map(BufferMapEx::from)
+}
+
+/// The origin, region and pitches of a rectangular region of a buffer in the byte units
+/// OpenCL expects.
+struct ByteRect {
+    origin: [usize; 3],
+    region: [usize; 3],
+    row_pitch: usize,
+    slice_pitch: usize,
+    /// The number of elements in the region.
+    len: usize,
+}
+
+impl ByteRect {
+    /// `origin` and `region` are `{x, y, z}` where `x` is in elements and `y` and `z` are in
+    /// rows and slices.
+    ///
+    /// Errors unless the region lies within the `byte_size` bytes of the buffer, so nothing
+    /// is allocated for a rect OpenCL would reject.
+    fn new(
+        origin: DimsEx,
+        region: DimsEx,
+        pitches: PitchesEx,
+        size_of_t: usize,
+        byte_size: usize,
+    ) -> OutputEx<ByteRect> {
+        match ByteRect::checked(origin, region, pitches, size_of_t) {
+            Some(rect) if rect.fits_in(byte_size) => Ok(rect),
+            _ => {
+                let (origin, region) = (origin.to_array(0), region.to_array(1));
+                Err(BoundsError::RectOutOfBounds(origin, region, byte_size).into())
+            }
+        }
+    }
+
+    /// Converts to bytes, resolving pitches of 0 to tightly packed ones like OpenCL does.
+    /// Returns `None` on overflow.
+    fn checked(
+        origin: DimsEx,
+        region: DimsEx,
+        pitches: PitchesEx,
+        size_of_t: usize,
+    ) -> Option<ByteRect> {
+        let [x, y, z] = origin.to_array(0);
+        let [w, h, d] = region.to_array(1);
+        let PitchesEx(row_pitch, slice_pitch) = pitches;
+        let len = w.checked_mul(h)?.checked_mul(d)?;
+        let w = w.checked_mul(size_of_t)?;
+        let row_pitch = match row_pitch {
+            0 => w,
+            pitch => pitch.checked_mul(size_of_t)?,
+        };
+        let slice_pitch = match slice_pitch {
+            0 => h.checked_mul(row_pitch)?,
+            pitch => pitch.checked_mul(size_of_t)?,
+        };
+        Some(ByteRect {
+            origin: [x.checked_mul(size_of_t)?, y, z],
+            region: [w, h, d],
+            row_pitch,
+            slice_pitch,
+            len,
+        })
+    }
+
+    /// Whether the rows and slices do not overlap and the last byte of the region
+    /// is within `byte_size`.
+    fn fits_in(&self, byte_size: usize) -> bool {
+        if self.len == 0 {
+            return true;
+        }
+        let [w, h, _] = self.region;
+        let packed = self.row_pitch >= w
+            && h.checked_mul(self.row_pitch)
+                .map_or(false, |min| self.slice_pitch >= min);
+        packed && self.end().map_or(false, |end| end <= byte_size)
+    }
+
+    /// One past the last byte of the region. Only meaningful for a non-empty region.
+    fn end(&self) -> Option<usize> {
+        let [x, y, z] = self.origin;
+        let [w, h, d] = self.region;
+        let slices = z.checked_add(d - 1)?.checked_mul(self.slice_pitch)?;
+        let rows = y.checked_add(h - 1)?.checked_mul(self.row_pitch)?;
+        slices.checked_add(rows)?.checked_add(x)?.checked_add(w)
+    }
+}
+
+// the host side of read and write rects is always tightly packed, starting at 0.
+const HOST_ORIGIN: [usize; 3] = [0, 0, 0];
+
+fn _read_buffer_rect<T: NumberEx>(
+    sess: &SessionEx,
+    buffer: BufferEx,
+    origin: DimsEx,
+    region: DimsEx,
+    pitches: PitchesEx,
+) -> OutputEx<ArrayEx> {
+    let size_of_t = std::mem::size_of::<T>();
+    let byte_size = buffer.wrapper().byte_size()?;
+    let rect = ByteRect::new(origin, region, pitches, size_of_t, byte_size)?;
+    if rect.len == 0 {
+        return Ok(ArrayEx::from(RuntimeNumberList::from_vec(Vec::<T>::new())));
+    }
+    let mut data = utils::vec_filled_with::<T>(T::zero(), rect.len);
+    let mut event_ptr: cl_event = std::ptr::null_mut();
+    let status = sess.with_queue(|queue| unsafe {
+        ffi::clEnqueueReadBufferRect(
+            queue,
+            buffer.wrapper().mem_ptr(),
+            ffi::CL_TRUE,
+            rect.origin.as_ptr(),
+            HOST_ORIGIN.as_ptr(),
+            rect.region.as_ptr(),
+            rect.row_pitch,
+            rect.slice_pitch,
+            0,
+            0,
+            data.as_mut_ptr() as *mut libc::c_void,
+            0,
+            std::ptr::null(),
+            &mut event_ptr,
+        )
+    });
+    check_status(status)?;
+    unsafe { Event::new(event_ptr, vec![buffer.clone()]) }.wait()?;
+    Ok(ArrayEx::from(RuntimeNumberList::from_vec(data)))
+}
+
+/// Reads the rectangular `region` of `buffer` at `origin` into a flat, row-major array.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn session_self_read_buffer_rect(
+    session: SessionEx,
+    buffer: BufferEx,
+    origin: DimsEx,
+    region: DimsEx,
+    pitches: PitchesEx,
+) -> OutputEx<ArrayEx> {
+    let num_type = buffer.number_type();
+    apply_number_type!(
+        num_type,
+        _read_buffer_rect,
+        [&session, buffer, origin, region, pitches]
+    )
+}
+
+fn _write_buffer_rect<T: NumberEx>(
+    sess: &SessionEx,
+    buffer: BufferEx,
+    origin: DimsEx,
+    region: DimsEx,
+    pitches: PitchesEx,
+    array: ArrayEx,
+) -> OutputEx<()> {
+    let size_of_t = std::mem::size_of::<T>();
+    let byte_size = buffer.wrapper().byte_size()?;
+    let rect = ByteRect::new(origin, region, pitches, size_of_t, byte_size)?;
+    let rt_list = array.read_lock();
+    let data: &[T] = rt_list.force_as_slice();
+    if data.len() != rect.len {
+        return Err(ArrayError::LengthMismatch(data.len(), rect.len).into());
+    }
+    if rect.len == 0 {
+        return Ok(());
+    }
+    let mut event_ptr: cl_event = std::ptr::null_mut();
+    let status = sess.with_queue(|queue| unsafe {
+        ffi::clEnqueueWriteBufferRect(
+            queue,
+            buffer.wrapper().mem_ptr(),
+            ffi::CL_TRUE,
+            rect.origin.as_ptr(),
+            HOST_ORIGIN.as_ptr(),
+            rect.region.as_ptr(),
+            rect.row_pitch,
+            rect.slice_pitch,
+            0,
+            0,
+            data.as_ptr() as *const libc::c_void,
+            0,
+            std::ptr::null(),
+            &mut event_ptr,
+        )
+    });
+    check_status(status)?;
+    unsafe { Event::new(event_ptr, vec![buffer.clone()]) }.wait()
+}
+
+/// Writes the flat, row-major `array` into the rectangular `region` of `buffer` at `origin`.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn session_self_write_array_to_buffer_rect(
+    session: SessionEx,
+    buffer: BufferEx,
+    origin: DimsEx,
+    region: DimsEx,
+    pitches: PitchesEx,
+    array: ArrayEx,
+) -> OutputEx<()> {
+    let num_type = buffer.number_type();
+    num_type.type_check(array.number_type())?;
+    apply_number_type!(
+        num_type,
+        _write_buffer_rect,
+        [&session, buffer, origin, region, pitches, array]
+    )
+}
+
+/// Copies the rectangular `region` at `src_origin` of `src` to `dst_origin` of `dst` on the device.
+#[allow(clippy::too_many_arguments)]
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn session_self_copy_buffer_rect(
+    session: SessionEx,
+    src: BufferEx,
+    dst: BufferEx,
+    src_origin: DimsEx,
+    dst_origin: DimsEx,
+    region: DimsEx,
+    src_pitches: PitchesEx,
+    dst_pitches: PitchesEx,
+) -> OutputEx<()> {
+    let num_type = src.number_type();
+    num_type.type_check(dst.number_type())?;
+    let size_of_t = num_type.size_of();
+    let src_size = src.wrapper().byte_size()?;
+    let dst_size = dst.wrapper().byte_size()?;
+    let src_rect = ByteRect::new(src_origin, region, src_pitches, size_of_t, src_size)?;
+    let dst_rect = ByteRect::new(dst_origin, region, dst_pitches, size_of_t, dst_size)?;
+    if src_rect.len == 0 {
+        return Ok(());
+    }
+    let mut event_ptr: cl_event = std::ptr::null_mut();
+    let status = session.with_queue(|queue| unsafe {
+        ffi::clEnqueueCopyBufferRect(
+            queue,
+            src.wrapper().mem_ptr(),
+            dst.wrapper().mem_ptr(),
+            src_rect.origin.as_ptr(),
+            dst_rect.origin.as_ptr(),
+            src_rect.region.as_ptr(),
+            src_rect.row_pitch,
+            src_rect.slice_pitch,
+            dst_rect.row_pitch,
+            dst_rect.slice_pitch,
+            0,
+            std::ptr::null(),
+            &mut event_ptr,
+        )
+    });
+    check_status(status)?;
+    unsafe { Event::new(event_ptr, vec![src.clone(), dst.clone()]) }.wait()
+}
+
+fn _write_buffer_profiled<T: NumberEx>(
+    sess: &SessionEx,
+    buffer: BufferEx,
+    array: ArrayEx,
+    cq_options: Option<CommandQueueOptionsEx>,
+) -> OutputEx<ProfilingInfoEx> {
+    let rt_list = array.read_lock();
+    let data: &[T] = rt_list.force_as_slice();
+    let offset = CommandQueueOptionsEx::offset_or_zero(&cq_options);
+    let event = enqueue_write(sess, &buffer, offset, data, true)?;
+    event.wait_for_profiling_info()
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn session_self_write_array_to_buffer_profiled(
+    session: SessionEx,
+    buffer: BufferEx,
+    array: ArrayEx,
+    cq_options: Option<CommandQueueOptionsEx>,
+) -> OutputEx<ProfilingInfoEx> {
+    let num_type = buffer.number_type();
+    num_type.type_check(array.number_type())?;
+    apply_number_type!(
+        num_type,
+        _write_buffer_profiled,
+        [&session, buffer, array, cq_options]
+    )
+}
+
+fn _read_buffer_profiled<T: NumberEx>(
+    sess: &SessionEx,
+    buffer: BufferEx,
+    cq_opts_ex: Option<CommandQueueOptionsEx>,
+) -> OutputEx<(ArrayEx, ProfilingInfoEx)> {
+    let offset = CommandQueueOptionsEx::offset_or_zero(&cq_opts_ex);
+    let len = buffer.wrapper().len()?.saturating_sub(offset);
+    let mut data = utils::vec_filled_with::<T>(T::zero(), len);
+    let event = enqueue_read(sess, &buffer, offset, &mut data[..], true)?;
+    let info = event.wait_for_profiling_info()?;
+    let array = ArrayEx::from(RuntimeNumberList::from_vec(data));
+    Ok((array, info))
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn session_self_read_buffer_profiled(
+    session: SessionEx,
+    buffer: BufferEx,
+    cq_opts_ex: Option<CommandQueueOptionsEx>,
+) -> OutputEx<(ArrayEx, ProfilingInfoEx)> {
+    let num_type = buffer.number_type();
+    apply_number_type!(
+        num_type,
+        _read_buffer_profiled,
+        [&session, buffer, cq_opts_ex]
+    )
 }
 
 #[rustler::nif(schedule = "DirtyCpu")]
@@ -277,19 +958,109 @@ pub fn session_self_execute_kernel_operation(
     session: SessionEx,
     kernel_op_ex: KernelOpEx,
 ) -> OutputEx<()> {
-    let num_type = kernel_op_ex.number_type();
+    let event = kernel_op_ex.enqueue(&session)?;
+    if kernel_op_ex.is_blocking() {
+        event.wait()
+    } else {
+        session.flush()
+    }
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn session_self_execute_kernel_operation_profiled(
+    session: SessionEx,
+    kernel_op_ex: KernelOpEx,
+) -> OutputEx<ProfilingInfoEx> {
+    kernel_op_ex.enqueue(&session)?.wait_for_profiling_info()
+}
+
+#[rustler::nif]
+pub fn session_self_execute_kernel_operation_async(
+    session: SessionEx,
+    kernel_op_ex: KernelOpEx,
+) -> OutputEx<EventEx> {
+    let event = kernel_op_ex.enqueue(&session)?;
+    session.flush()?;
+    Ok(EventEx::from(event))
+}
+
+#[rustler::nif]
+pub fn session_self_execute_kernel_operation_notify<'a>(
+    session: SessionEx,
+    kernel_op_ex: KernelOpEx,
+    pid: Pid,
+    reference: Term<'a>,
+) -> OutputEx<()> {
+    let event = EventEx::from(kernel_op_ex.enqueue(&session)?);
+    event.notify(pid, reference, encode_status_result)?;
+    session.flush()
+}
+
+fn _enqueue_read_buffer_notify<'a, T: NumberEx>(
+    sess: &SessionEx,
+    buffer: BufferEx,
+    pid: Pid,
+    reference: Term<'a>,
+) -> OutputEx<()> {
+    let len = buffer.wrapper().len()?;
+    let mut rt_list = RuntimeNumberList::from_vec(utils::vec_filled_with::<T>(T::zero(), len));
+    let data = rt_list.force_as_slice_mut::<T>();
+    let event = EventEx::from(enqueue_read(sess, &buffer, 0, data, false)?);
+    // the callback holds the host data so it outlives the read.
+    let array = ArrayEx::from(Array::new(rt_list));
+    let result = array.clone();
+    let notified = event.notify(pid, reference, move |env: Env, status: cl_int| {
+        match check_status(status.min(0)) {
+            Ok(()) => (atoms::ok(), result).encode(env),
+            Err(e) => (atoms::error(), e).encode(env),
+        }
+    });
+    if let Err(e) = notified {
+        // the callback was dropped; the device may still be writing into the array.
+        let _ = event.event().wait();
+        return Err(e);
+    }
+    sess.flush()
+}
+
+/// Dirty like the blocking reads, since the host array for the whole buffer is
+/// allocated before the read is enqueued.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn session_self_read_buffer_notify<'a>(
+    session: SessionEx,
+    buffer: BufferEx,
+    pid: Pid,
+    reference: Term<'a>,
+) -> OutputEx<()> {
+    let num_type = buffer.number_type();
     apply_number_type!(
         num_type,
-        _execute_sync_kernel_operation,
-        [&session, kernel_op_ex]
+        _enqueue_read_buffer_notify,
+        [&session, buffer, pid, reference]
     )
 }
 
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn session_self_launch_kernel(
+    session: SessionEx,
+    kernel: KernelEx,
+    work: WorkEx,
+) -> OutputEx<()> {
+    kernel.bound_kernel().enqueue(&session, &work)?.wait()
+}
+
+#[rustler::nif]
+pub fn session_self_launch_kernel_async(
+    session: SessionEx,
+    kernel: KernelEx,
+    work: WorkEx,
+) -> OutputEx<EventEx> {
+    let event = kernel.bound_kernel().enqueue(&session, &work)?;
+    session.flush()?;
+    Ok(EventEx::from(event))
+}
+
 #[rustler::nif]
 pub fn session_self_create_copy(session: SessionEx) -> OutputEx<SessionEx> {
-    session
-        .native()
-        .create_copy()
-        .map(|s| SessionEx::new(s))
-        .map_err(From::from)
+    session.create_copy()
 }
@@ -107,7 +107,24 @@ rustler::init! {
         session_ex::session_self_create_buffer,
         session_ex::session_self_write_array_to_buffer,
         session_ex::session_self_read_buffer,
+        session_ex::session_self_read_buffer_range,
+        session_ex::session_self_read_buffer_into,
+        session_ex::session_self_write_array_to_buffer_range,
+        session_ex::session_self_read_buffer_rect,
+        session_ex::session_self_write_array_to_buffer_rect,
+        session_ex::session_self_copy_buffer_rect,
+        session_ex::session_self_copy_buffer,
+        session_ex::session_self_fill_buffer,
+        session_ex::session_self_map_buffer,
+        session_ex::session_self_write_array_to_buffer_profiled,
+        session_ex::session_self_read_buffer_profiled,
         session_ex::session_self_execute_kernel_operation,
+        session_ex::session_self_execute_kernel_operation_profiled,
+        session_ex::session_self_execute_kernel_operation_async,
+        session_ex::session_self_execute_kernel_operation_notify,
+        session_ex::session_self_read_buffer_notify,
+        session_ex::session_self_launch_kernel,
+        session_ex::session_self_launch_kernel_async,
         session_ex::session_self_create_copy,
         // session_ex::session_self_device_name,
         // session_ex::session_self_device_opencl_c_version,
@@ -166,6 +183,10 @@ rustler::init! {
         // ARRAY
         array_ex::array_new,
         array_ex::array_new_filled_with,
+        array_ex::array_from_binary,
+        array_ex::array_to_binary,
+        array_ex::array_to_shared_binary,
+        array_ex::array_is_shared,
         array_ex::array_push,
         array_ex::array_data,
         array_ex::array_length,
@@ -173,13 +194,53 @@ rustler::init! {
         array_ex::array_extend_from_array,
         array_ex::array_number_type,
         array_ex::array_cast,
+        array_ex::array_cast_with_mode,
+        array_ex::array_dims,
+        array_ex::array_strides,
+        array_ex::array_reshape,
+        array_ex::array_transpose,
+        array_ex::array_slice_axis,
+        array_ex::array_elementwise,
+        array_ex::array_elementwise_scalar,
+        array_ex::array_reduce,
+        array_ex::array_get,
+        array_ex::array_set,
+        array_ex::array_slice,
+        array_ex::array_put_slice_from_list,
+        array_ex::array_put_slice_from_array,
 
         // DEVICE_BUFFER
         buffer_ex::buffer_self_length,
         buffer_ex::buffer_self_number_type,
+        buffer_ex::buffer_self_sub_buffer,
+        buffer_ex::buffer_self_is_sub_buffer,
+        buffer_map_ex::buffer_map_self_to_array,
+        buffer_map_ex::buffer_map_self_to_shared_binary,
+        buffer_map_ex::buffer_map_self_write_array,
+        buffer_map_ex::buffer_map_self_unmap,
+        buffer_map_ex::buffer_map_self_is_mapped,
+        buffer_map_ex::buffer_map_self_length,
+        buffer_map_ex::buffer_map_self_number_type,
         // device_buffer_ex::buffer_to_array,
         // device_buffer_ex::buffer_reference_count,
 
+        // EVENT
+        event_ex::event_self_wait,
+        event_ex::event_self_status,
+        event_ex::event_self_profiling_info,
+        event_ex::event_self_on_complete,
+
+        // PROGRAM
+        program_ex::program_create,
+        program_ex::program_self_kernel_names,
+        program_ex::program_self_create_kernel,
+
+        // KERNEL
+        kernel_ex::kernel_self_name,
+        kernel_ex::kernel_self_num_args,
+        kernel_ex::kernel_self_arg_info,
+        kernel_ex::kernel_self_set_arg,
+
     ],
     load = load
 }
@@ -135,6 +135,9 @@ macro_rules! apply_number_type {
             NumberType::F64 => $func::<f64>($( $arg ),*),
             NumberType::Usize => $func::<usize>($( $arg ),*),
             NumberType::Isize => $func::<isize>($( $arg ),*),
+            NumberType::F16 => $func::<$crate::F16>($( $arg ),*),
+            NumberType::C32 => $func::<$crate::C32>($( $arg ),*),
+            NumberType::C64 => $func::<$crate::C64>($( $arg ),*),
         }
     }
 }
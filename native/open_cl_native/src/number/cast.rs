@@ -0,0 +1,60 @@
+use rustler::NifUnitEnum;
+
+use crate::{C32, C64, F16};
+
+/// How a number that doesn't fit in the target type is cast.
+#[derive(NifUnitEnum, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CastMode {
+    /// Like Rust's `as`: integers wrap around and floats are truncated, with NaN becoming 0.
+    Wrapping,
+    /// Out of range numbers become the closest number of the target type. NaN becomes 0
+    /// for integer types and stays NaN for floats.
+    Saturating,
+    /// Out of range numbers, NaNs cast to integer types and complex numbers with an
+    /// imaginary part are errors. NaN and infinities cast to floats as themselves.
+    Checked,
+}
+
+/// The lowest and highest finite numbers of a type, used by `CastMode::Saturating`.
+pub trait CastBounds {
+    fn lowest() -> Self;
+    fn highest() -> Self;
+}
+
+macro_rules! impl_cast_bounds {
+    ($t:ty, $lowest:expr, $highest:expr) => {
+        impl CastBounds for $t {
+            fn lowest() -> $t {
+                $lowest
+            }
+
+            fn highest() -> $t {
+                $highest
+            }
+        }
+    };
+}
+
+impl_cast_bounds!(u8, u8::min_value(), u8::max_value());
+impl_cast_bounds!(i8, i8::min_value(), i8::max_value());
+impl_cast_bounds!(u16, u16::min_value(), u16::max_value());
+impl_cast_bounds!(i16, i16::min_value(), i16::max_value());
+impl_cast_bounds!(u32, u32::min_value(), u32::max_value());
+impl_cast_bounds!(i32, i32::min_value(), i32::max_value());
+impl_cast_bounds!(u64, u64::min_value(), u64::max_value());
+impl_cast_bounds!(i64, i64::min_value(), i64::max_value());
+impl_cast_bounds!(usize, usize::min_value(), usize::max_value());
+impl_cast_bounds!(isize, isize::min_value(), isize::max_value());
+impl_cast_bounds!(f32, std::f32::MIN, std::f32::MAX);
+impl_cast_bounds!(f64, std::f64::MIN, std::f64::MAX);
+impl_cast_bounds!(F16, F16::from_f32(-65504.0), F16::from_f32(65504.0));
+impl_cast_bounds!(
+    C32,
+    C32::new(std::f32::MIN, 0.0),
+    C32::new(std::f32::MAX, 0.0)
+);
+impl_cast_bounds!(
+    C64,
+    C64::new(std::f64::MIN, 0.0),
+    C64::new(std::f64::MAX, 0.0)
+);
@@ -0,0 +1,184 @@
+use std::fmt;
+use std::ops::{Add, Div, Mul, Sub};
+
+use num_complex::Complex;
+use num_traits::identities::Zero;
+use num_traits::{FromPrimitive, NumCast, ToPrimitive};
+use opencl_core::ll::{ClNumber, KernelArg, SizeAndPtr};
+use rustler::{Decoder, Encoder, Env, NifResult, Term};
+
+/// Decodes an Elixir integer or float as an `f64`.
+fn decode_float(term: Term) -> NifResult<f64> {
+    match term.decode::<f64>() {
+        Ok(value) => Ok(value),
+        Err(_) => term.decode::<i64>().map(|value| value as f64),
+    }
+}
+
+macro_rules! define_complex {
+    ($name:ident, $float:ty, $doc:expr) => {
+        #[doc = $doc]
+        ///
+        /// Encoded to and decoded from Elixir as a `{re, im}` tuple. Converting
+        /// a complex number to a real number type keeps only the real part.
+        #[repr(transparent)]
+        #[derive(Clone, Copy, PartialEq, Default)]
+        pub struct $name(Complex<$float>);
+
+        impl $name {
+            pub fn new(re: $float, im: $float) -> $name {
+                $name(Complex::new(re, im))
+            }
+
+            pub fn re(&self) -> $float {
+                self.0.re
+            }
+
+            pub fn im(&self) -> $float {
+                self.0.im
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{{{:?}, {:?}}}", self.re(), self.im())
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}+{}i", self.re(), self.im())
+            }
+        }
+
+        impl Add for $name {
+            type Output = $name;
+
+            fn add(self, other: $name) -> $name {
+                $name(self.0 + other.0)
+            }
+        }
+
+        impl Sub for $name {
+            type Output = $name;
+
+            fn sub(self, other: $name) -> $name {
+                $name(self.0 - other.0)
+            }
+        }
+
+        impl Mul for $name {
+            type Output = $name;
+
+            fn mul(self, other: $name) -> $name {
+                $name(self.0 * other.0)
+            }
+        }
+
+        impl Div for $name {
+            type Output = $name;
+
+            fn div(self, other: $name) -> $name {
+                $name(self.0 / other.0)
+            }
+        }
+
+        impl Zero for $name {
+            fn zero() -> $name {
+                $name(Complex::zero())
+            }
+
+            fn is_zero(&self) -> bool {
+                self.0.is_zero()
+            }
+        }
+
+        impl ToPrimitive for $name {
+            fn to_i64(&self) -> Option<i64> {
+                self.re().to_i64()
+            }
+
+            fn to_u64(&self) -> Option<u64> {
+                self.re().to_u64()
+            }
+
+            fn to_f32(&self) -> Option<f32> {
+                self.re().to_f32()
+            }
+
+            fn to_f64(&self) -> Option<f64> {
+                self.re().to_f64()
+            }
+        }
+
+        impl FromPrimitive for $name {
+            fn from_i64(n: i64) -> Option<$name> {
+                Some($name::new(n as $float, 0.0))
+            }
+
+            fn from_u64(n: u64) -> Option<$name> {
+                Some($name::new(n as $float, 0.0))
+            }
+
+            fn from_f64(n: f64) -> Option<$name> {
+                Some($name::new(n as $float, 0.0))
+            }
+        }
+
+        impl NumCast for $name {
+            fn from<T: ToPrimitive>(n: T) -> Option<$name> {
+                n.to_f64().map(|re| $name::new(re as $float, 0.0))
+            }
+        }
+
+        unsafe impl ClNumber for $name {}
+
+        unsafe impl KernelArg for $name {
+            unsafe fn as_kernel_arg(&self) -> SizeAndPtr<*mut libc::c_void> {
+                SizeAndPtr(
+                    std::mem::size_of::<$name>(),
+                    self as *const $name as *mut libc::c_void,
+                )
+            }
+        }
+
+        impl Encoder for $name {
+            fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+                (self.re() as f64, self.im() as f64).encode(env)
+            }
+        }
+
+        impl<'a> Decoder<'a> for $name {
+            fn decode(term: Term<'a>) -> NifResult<$name> {
+                let (re, im): (Term<'a>, Term<'a>) = term.decode()?;
+                Ok($name::new(
+                    decode_float(re)? as $float,
+                    decode_float(im)? as $float,
+                ))
+            }
+        }
+    };
+}
+
+define_complex!(
+    C32,
+    f32,
+    "A complex number of two `f32`s (an OpenCL `float2`)."
+);
+define_complex!(
+    C64,
+    f64,
+    "A complex number of two `f64`s (an OpenCL `double2`)."
+);
+
+impl From<C32> for C64 {
+    fn from(num: C32) -> C64 {
+        C64::new(f64::from(num.re()), f64::from(num.im()))
+    }
+}
+
+impl From<C64> for C32 {
+    fn from(num: C64) -> C32 {
+        C32::new(num.re() as f32, num.im() as f32)
+    }
+}
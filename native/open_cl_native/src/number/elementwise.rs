@@ -0,0 +1,112 @@
+use rustler::NifUnitEnum;
+
+use crate::{NumberType, C32, C64, F16};
+
+/// An element-wise operation on two numbers of the same type.
+#[derive(NifUnitEnum, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ElementwiseOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Min,
+    Max,
+}
+
+impl ElementwiseOp {
+    /// Complex numbers have no ordering, so they support neither `Min` nor `Max`.
+    pub fn supports(&self, number_type: NumberType) -> bool {
+        match (self, number_type) {
+            (ElementwiseOp::Min, NumberType::C32) => false,
+            (ElementwiseOp::Min, NumberType::C64) => false,
+            (ElementwiseOp::Max, NumberType::C32) => false,
+            (ElementwiseOp::Max, NumberType::C64) => false,
+            _ => true,
+        }
+    }
+}
+
+/// Applies an `ElementwiseOp` the way OpenCL C would.
+///
+/// Integer arithmetic wraps on overflow. Returns `None` for an integer division
+/// by zero and for ops that the type does not support.
+pub trait Elementwise: Sized {
+    fn elementwise(self, op: ElementwiseOp, other: Self) -> Option<Self>;
+}
+
+macro_rules! impl_elementwise_int {
+    ($t:ty) => {
+        impl Elementwise for $t {
+            fn elementwise(self, op: ElementwiseOp, other: $t) -> Option<$t> {
+                match op {
+                    ElementwiseOp::Add => Some(self.wrapping_add(other)),
+                    ElementwiseOp::Sub => Some(self.wrapping_sub(other)),
+                    ElementwiseOp::Mul => Some(self.wrapping_mul(other)),
+                    ElementwiseOp::Div if other == 0 => None,
+                    ElementwiseOp::Div => Some(self.wrapping_div(other)),
+                    ElementwiseOp::Min => Some(self.min(other)),
+                    ElementwiseOp::Max => Some(self.max(other)),
+                }
+            }
+        }
+    };
+}
+
+impl_elementwise_int!(u8);
+impl_elementwise_int!(i8);
+impl_elementwise_int!(u16);
+impl_elementwise_int!(i16);
+impl_elementwise_int!(u32);
+impl_elementwise_int!(i32);
+impl_elementwise_int!(u64);
+impl_elementwise_int!(i64);
+impl_elementwise_int!(usize);
+impl_elementwise_int!(isize);
+
+macro_rules! impl_elementwise_float {
+    ($t:ty) => {
+        impl Elementwise for $t {
+            fn elementwise(self, op: ElementwiseOp, other: $t) -> Option<$t> {
+                let result = match op {
+                    ElementwiseOp::Add => self + other,
+                    ElementwiseOp::Sub => self - other,
+                    ElementwiseOp::Mul => self * other,
+                    ElementwiseOp::Div => self / other,
+                    ElementwiseOp::Min => self.min(other),
+                    ElementwiseOp::Max => self.max(other),
+                };
+                Some(result)
+            }
+        }
+    };
+}
+
+impl_elementwise_float!(f32);
+impl_elementwise_float!(f64);
+
+impl Elementwise for F16 {
+    fn elementwise(self, op: ElementwiseOp, other: F16) -> Option<F16> {
+        f32::from(self)
+            .elementwise(op, f32::from(other))
+            .map(F16::from_f32)
+    }
+}
+
+macro_rules! impl_elementwise_complex {
+    ($t:ty) => {
+        impl Elementwise for $t {
+            fn elementwise(self, op: ElementwiseOp, other: $t) -> Option<$t> {
+                match op {
+                    ElementwiseOp::Add => Some(self + other),
+                    ElementwiseOp::Sub => Some(self - other),
+                    ElementwiseOp::Mul => Some(self * other),
+                    ElementwiseOp::Div => Some(self / other),
+                    ElementwiseOp::Min | ElementwiseOp::Max => None,
+                }
+            }
+        }
+    };
+}
+
+impl_elementwise_complex!(C32);
+impl_elementwise_complex!(C64);
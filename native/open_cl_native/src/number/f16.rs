@@ -0,0 +1,147 @@
+use std::fmt;
+use std::ops::Add;
+
+use half::f16 as HalfF16;
+use num_traits::identities::Zero;
+use num_traits::{FromPrimitive, NumCast, ToPrimitive};
+use opencl_core::ll::{ClNumber, KernelArg, SizeAndPtr};
+use rustler::{Decoder, Encoder, Env, NifResult, Term};
+
+/// An OpenCL `half`.
+///
+/// Elixir only has 64-bit floats, so `F16` is encoded and decoded as a float.
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct F16(HalfF16);
+
+impl F16 {
+    pub fn from_f32(value: f32) -> F16 {
+        F16(HalfF16::from_f32(value))
+    }
+
+    pub fn from_f64(value: f64) -> F16 {
+        F16(HalfF16::from_f64(value))
+    }
+
+    pub fn from_bits(bits: u16) -> F16 {
+        F16(HalfF16::from_bits(bits))
+    }
+
+    pub fn to_bits(self) -> u16 {
+        self.0.to_bits()
+    }
+}
+
+impl From<F16> for f32 {
+    fn from(num: F16) -> f32 {
+        num.0.to_f32()
+    }
+}
+
+impl From<F16> for f64 {
+    fn from(num: F16) -> f64 {
+        num.0.to_f64()
+    }
+}
+
+impl fmt::Debug for F16 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}f16", f32::from(*self))
+    }
+}
+
+impl fmt::Display for F16 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", f32::from(*self))
+    }
+}
+
+impl Add for F16 {
+    type Output = F16;
+
+    fn add(self, other: F16) -> F16 {
+        F16::from_f32(f32::from(self) + f32::from(other))
+    }
+}
+
+impl Zero for F16 {
+    fn zero() -> F16 {
+        F16(HalfF16::from_bits(0))
+    }
+
+    fn is_zero(&self) -> bool {
+        f32::from(*self) == 0.0
+    }
+}
+
+impl ToPrimitive for F16 {
+    fn to_i64(&self) -> Option<i64> {
+        f32::from(*self).to_i64()
+    }
+
+    fn to_u64(&self) -> Option<u64> {
+        f32::from(*self).to_u64()
+    }
+
+    fn to_f32(&self) -> Option<f32> {
+        Some(f32::from(*self))
+    }
+
+    fn to_f64(&self) -> Option<f64> {
+        Some(f64::from(*self))
+    }
+}
+
+impl FromPrimitive for F16 {
+    fn from_i64(n: i64) -> Option<F16> {
+        Some(F16::from_f64(n as f64))
+    }
+
+    fn from_u64(n: u64) -> Option<F16> {
+        Some(F16::from_f64(n as f64))
+    }
+
+    fn from_f32(n: f32) -> Option<F16> {
+        Some(F16::from_f32(n))
+    }
+
+    fn from_f64(n: f64) -> Option<F16> {
+        Some(F16::from_f64(n))
+    }
+}
+
+impl NumCast for F16 {
+    /// Like the primitive floats, `None` for a finite number outside the range of a
+    /// half, while infinities and NaN cast to themselves.
+    fn from<T: ToPrimitive>(n: T) -> Option<F16> {
+        let val = n.to_f64()?;
+        if val.is_finite() && val.abs() > 65504.0 {
+            return None;
+        }
+        Some(F16::from_f64(val))
+    }
+}
+
+unsafe impl ClNumber for F16 {}
+
+unsafe impl KernelArg for F16 {
+    unsafe fn as_kernel_arg(&self) -> SizeAndPtr<*mut libc::c_void> {
+        SizeAndPtr(
+            std::mem::size_of::<F16>(),
+            self as *const F16 as *mut libc::c_void,
+        )
+    }
+}
+
+impl Encoder for F16 {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        f64::from(*self).encode(env)
+    }
+}
+
+impl<'a> Decoder<'a> for F16 {
+    fn decode(term: Term<'a>) -> NifResult<F16> {
+        let value: f64 = term.decode()?;
+        Ok(F16::from_f64(value))
+    }
+}
@@ -0,0 +1,106 @@
+use std::mem::size_of;
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use crate::{C32, C64, F16};
+
+/// Reads and writes a number as raw little-endian bytes.
+///
+/// `read_le` expects exactly `size_of::<Self>()` bytes.
+pub trait LeBytes: Sized {
+    fn read_le(bytes: &[u8]) -> Self;
+    fn write_le(&self, out: &mut [u8]);
+}
+
+impl LeBytes for u8 {
+    fn read_le(bytes: &[u8]) -> u8 {
+        bytes[0]
+    }
+
+    fn write_le(&self, out: &mut [u8]) {
+        out[0] = *self;
+    }
+}
+
+impl LeBytes for i8 {
+    fn read_le(bytes: &[u8]) -> i8 {
+        bytes[0] as i8
+    }
+
+    fn write_le(&self, out: &mut [u8]) {
+        out[0] = *self as u8;
+    }
+}
+
+macro_rules! impl_le_bytes {
+    ($t:ty, $read:ident, $write:ident) => {
+        impl LeBytes for $t {
+            fn read_le(bytes: &[u8]) -> $t {
+                LittleEndian::$read(bytes)
+            }
+
+            fn write_le(&self, out: &mut [u8]) {
+                LittleEndian::$write(out, *self)
+            }
+        }
+    };
+}
+
+impl_le_bytes!(u16, read_u16, write_u16);
+impl_le_bytes!(i16, read_i16, write_i16);
+impl_le_bytes!(u32, read_u32, write_u32);
+impl_le_bytes!(i32, read_i32, write_i32);
+impl_le_bytes!(f32, read_f32, write_f32);
+impl_le_bytes!(u64, read_u64, write_u64);
+impl_le_bytes!(i64, read_i64, write_i64);
+impl_le_bytes!(f64, read_f64, write_f64);
+
+impl LeBytes for usize {
+    fn read_le(bytes: &[u8]) -> usize {
+        LittleEndian::read_uint(bytes, size_of::<usize>()) as usize
+    }
+
+    fn write_le(&self, out: &mut [u8]) {
+        LittleEndian::write_uint(out, *self as u64, size_of::<usize>())
+    }
+}
+
+impl LeBytes for isize {
+    fn read_le(bytes: &[u8]) -> isize {
+        LittleEndian::read_int(bytes, size_of::<isize>()) as isize
+    }
+
+    fn write_le(&self, out: &mut [u8]) {
+        LittleEndian::write_int(out, *self as i64, size_of::<isize>())
+    }
+}
+
+impl LeBytes for F16 {
+    fn read_le(bytes: &[u8]) -> F16 {
+        F16::from_bits(LittleEndian::read_u16(bytes))
+    }
+
+    fn write_le(&self, out: &mut [u8]) {
+        LittleEndian::write_u16(out, self.to_bits())
+    }
+}
+
+macro_rules! impl_le_bytes_complex {
+    ($t:ident, $float:ty) => {
+        impl LeBytes for $t {
+            fn read_le(bytes: &[u8]) -> $t {
+                let (re, im) = bytes.split_at(size_of::<$float>());
+                $t::new(<$float>::read_le(re), <$float>::read_le(im))
+            }
+
+            fn write_le(&self, out: &mut [u8]) {
+                let (re, im) = out.split_at_mut(size_of::<$float>());
+                self.re().write_le(re);
+                self.im().write_le(im);
+            }
+        }
+    };
+}
+
+impl_le_bytes_complex!(C32, f32);
+impl_le_bytes_complex!(C64, f64);
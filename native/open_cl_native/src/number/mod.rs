@@ -1,5 +1,18 @@
+pub mod cast;
+pub mod complex;
+pub mod elementwise;
+pub mod f16;
+pub mod le_bytes;
 pub mod number_type;
+pub mod reduction;
+
+pub use cast::{CastBounds, CastMode};
+pub use complex::{C32, C64};
+pub use elementwise::{Elementwise, ElementwiseOp};
+pub use f16::F16;
+pub use le_bytes::LeBytes;
 pub use number_type::*;
+pub use reduction::{ReductionOp, Widen};
 
 // pub mod mut_slice_t;
 // pub mod slice_t;
@@ -2,12 +2,21 @@ use opencl_core::ClNumber;
 
 use rustler::NifUnitEnum;
 
-use crate::OutputEx;
+use crate::{OutputEx, C32, C64, F16};
 
 #[derive(Debug, Fail, PartialEq, Eq, Clone)]
 pub enum NumberTypeError {
     #[fail(display = "Number Type Mismatch - {:?} vs {:?}", _0, _1)]
     TypeMismatch(NumberType, NumberType),
+
+    #[fail(display = "Number {} at index {} can't be cast to {:?}", _1, _0, _2)]
+    CastFailed(usize, String, NumberType),
+
+    #[fail(display = "Number {} does not fit in {:?}", _0, _1)]
+    DoesNotFit(String, NumberType),
+
+    #[fail(display = "Byte size {} is not a multiple of the {:?} size", _1, _0)]
+    InvalidByteSize(NumberType, usize),
 }
 
 #[derive(NifUnitEnum, Debug, PartialEq, Eq, Clone, Copy)]
@@ -24,6 +33,9 @@ pub enum NumberType {
     F64,
     Usize,
     Isize,
+    F16,
+    C32,
+    C64,
 }
 
 impl NumberType {
@@ -34,6 +46,20 @@ impl NumberType {
         Ok(t)
     }
 
+    pub fn is_complex(&self) -> bool {
+        match self {
+            NumberType::C32 | NumberType::C64 => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_float(&self) -> bool {
+        match self {
+            NumberType::F16 | NumberType::F32 | NumberType::F64 => true,
+            _ => false,
+        }
+    }
+
     pub fn mismatch_error(self, t: NumberType) -> NumberTypeError {
         NumberTypeError::TypeMismatch(self, t)
     }
@@ -65,6 +91,9 @@ impl NumberType {
             NumberType::F64 => size_of::<f64>(),
             NumberType::Usize => size_of::<usize>(),
             NumberType::Isize => size_of::<isize>(),
+            NumberType::F16 => size_of::<F16>(),
+            NumberType::C32 => size_of::<C32>(),
+            NumberType::C64 => size_of::<C64>(),
         }
     }
 }
@@ -121,6 +150,9 @@ impl_number_typed_t!(i64, I64);
 impl_number_typed_t!(f64, F64);
 impl_number_typed_t!(usize, Usize);
 impl_number_typed_t!(isize, Isize);
+impl_number_typed_t!(F16, F16);
+impl_number_typed_t!(C32, C32);
+impl_number_typed_t!(C64, C64);
 
 impl NumberTyped for NumberType {
     fn number_type(&self) -> NumberType {
@@ -0,0 +1,222 @@
+use rustler::NifUnitEnum;
+
+use crate::{ArrayError, ErrorEx, NumEx, OutputEx, C32, C64, F16};
+
+/// A reduction of all the numbers of an array to a single value.
+#[derive(NifUnitEnum, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ReductionOp {
+    Sum,
+    Product,
+    Min,
+    Max,
+    Mean,
+    Argmin,
+    Argmax,
+}
+
+impl ReductionOp {
+    pub fn needs_ordering(&self) -> bool {
+        match self {
+            ReductionOp::Sum | ReductionOp::Product | ReductionOp::Mean => false,
+            _ => true,
+        }
+    }
+}
+
+/// A number widened to the accumulator type of its kind.
+///
+/// Integers of any size are accumulated as `i128` so sums of small integers
+/// can't overflow and floats are accumulated as `f64`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Wide {
+    Int(i128),
+    Float(f64),
+    Complex(C64),
+}
+
+pub trait Widen {
+    fn widen(&self) -> Wide;
+}
+
+macro_rules! impl_widen {
+    ($t:ty, $variant:ident, $wide:ty) => {
+        impl Widen for $t {
+            fn widen(&self) -> Wide {
+                Wide::$variant(*self as $wide)
+            }
+        }
+    };
+}
+
+impl_widen!(u8, Int, i128);
+impl_widen!(i8, Int, i128);
+impl_widen!(u16, Int, i128);
+impl_widen!(i16, Int, i128);
+impl_widen!(u32, Int, i128);
+impl_widen!(i32, Int, i128);
+impl_widen!(u64, Int, i128);
+impl_widen!(i64, Int, i128);
+impl_widen!(usize, Int, i128);
+impl_widen!(isize, Int, i128);
+impl_widen!(f32, Float, f64);
+impl_widen!(f64, Float, f64);
+
+impl Widen for F16 {
+    fn widen(&self) -> Wide {
+        Wide::Float(f64::from(*self))
+    }
+}
+
+impl Widen for C32 {
+    fn widen(&self) -> Wide {
+        Wide::Complex((*self).into())
+    }
+}
+
+impl Widen for C64 {
+    fn widen(&self) -> Wide {
+        Wide::Complex(*self)
+    }
+}
+
+impl Wide {
+    fn is_nan(&self) -> bool {
+        match self {
+            Wide::Float(f) => f.is_nan(),
+            Wide::Complex(c) => c.re().is_nan() || c.im().is_nan(),
+            Wide::Int(..) => false,
+        }
+    }
+
+    fn checked_add(self, other: Wide) -> OutputEx<Wide> {
+        match (self, other) {
+            (Wide::Int(a), Wide::Int(b)) => a.checked_add(b).map(Wide::Int).ok_or_else(overflow),
+            (Wide::Float(a), Wide::Float(b)) => Ok(Wide::Float(a + b)),
+            (Wide::Complex(a), Wide::Complex(b)) => Ok(Wide::Complex(a + b)),
+            _ => unreachable!("numbers of one array widen to the same kind"),
+        }
+    }
+
+    fn checked_mul(self, other: Wide) -> OutputEx<Wide> {
+        match (self, other) {
+            (Wide::Int(a), Wide::Int(b)) => a.checked_mul(b).map(Wide::Int).ok_or_else(overflow),
+            (Wide::Float(a), Wide::Float(b)) => Ok(Wide::Float(a * b)),
+            (Wide::Complex(a), Wide::Complex(b)) => Ok(Wide::Complex(a * b)),
+            _ => unreachable!("numbers of one array widen to the same kind"),
+        }
+    }
+
+    /// Compares two non-NaN numbers; complex numbers have no ordering.
+    fn is_less_than(&self, other: &Wide) -> OutputEx<bool> {
+        match (self, other) {
+            (Wide::Int(a), Wide::Int(b)) => Ok(a < b),
+            (Wide::Float(a), Wide::Float(b)) => Ok(a < b),
+            _ => Err(ArrayError::UnorderedReduction.into()),
+        }
+    }
+
+    fn into_num_ex(self) -> OutputEx<NumEx> {
+        match self {
+            Wide::Int(n) if n >= i64::min_value() as i128 && n <= i64::max_value() as i128 => {
+                Ok(NumEx::I64(n as i64))
+            }
+            Wide::Int(n) if n >= 0 && n <= u64::max_value() as i128 => Ok(NumEx::U64(n as u64)),
+            Wide::Int(..) => Err(overflow()),
+            Wide::Float(f) if f.is_finite() => Ok(NumEx::F64(f)),
+            Wide::Complex(c) if c.re().is_finite() && c.im().is_finite() => Ok(NumEx::C64(c)),
+            _ => Err(ArrayError::NonFiniteReduction.into()),
+        }
+    }
+}
+
+fn overflow() -> ErrorEx {
+    ArrayError::ReductionOverflow.into()
+}
+
+fn mean(sum: Wide, count: usize) -> Wide {
+    match sum {
+        Wide::Int(n) => Wide::Float(n as f64 / count as f64),
+        Wide::Float(f) => Wide::Float(f / count as f64),
+        Wide::Complex(c) => Wide::Complex(C64::new(c.re() / count as f64, c.im() / count as f64)),
+    }
+}
+
+/// Finds the index of the smallest (or, with `max`, largest) number.
+///
+/// The first NaN wins unless NaNs are skipped, like NumPy's `argmin` and `argmax`.
+fn arg_extreme<I>(values: I, max: bool, skip_nan: bool) -> OutputEx<Option<(usize, Wide)>>
+where
+    I: Iterator<Item = Wide>,
+{
+    let mut best: Option<(usize, Wide)> = None;
+    for (i, value) in values.enumerate() {
+        if value.is_nan() {
+            if skip_nan {
+                continue;
+            }
+            return Ok(Some((i, value)));
+        }
+        let is_better = match &best {
+            None => true,
+            Some((_, current)) if max => current.is_less_than(&value)?,
+            Some((_, current)) => value.is_less_than(current)?,
+        };
+        if is_better {
+            best = Some((i, value));
+        }
+    }
+    Ok(best)
+}
+
+/// Reduces `values` with `op`. With `skip_nan` NaNs are left out of the reduction.
+///
+/// `zero` is the zero of the values' kind.
+pub fn reduce<I>(values: I, zero: Wide, op: ReductionOp, skip_nan: bool) -> OutputEx<NumEx>
+where
+    I: Iterator<Item = Wide>,
+{
+    if let (Wide::Complex(..), true) = (zero, op.needs_ordering()) {
+        return Err(ArrayError::UnorderedReduction.into());
+    }
+    match op {
+        ReductionOp::Argmin | ReductionOp::Argmax => {
+            let max = op == ReductionOp::Argmax;
+            match arg_extreme(values, max, skip_nan)? {
+                Some((i, _)) => Ok(NumEx::Usize(i)),
+                None => Err(ArrayError::EmptyReduction.into()),
+            }
+        }
+        ReductionOp::Min | ReductionOp::Max => {
+            let max = op == ReductionOp::Max;
+            match arg_extreme(values, max, skip_nan)? {
+                Some((_, value)) => value.into_num_ex(),
+                None => Err(ArrayError::EmptyReduction.into()),
+            }
+        }
+        ReductionOp::Sum => {
+            let mut values = values.filter(|value| !(skip_nan && value.is_nan()));
+            values.try_fold(zero, Wide::checked_add)?.into_num_ex()
+        }
+        ReductionOp::Product => {
+            let one = match zero {
+                Wide::Int(..) => Wide::Int(1),
+                Wide::Float(..) => Wide::Float(1.0),
+                Wide::Complex(..) => Wide::Complex(C64::new(1.0, 0.0)),
+            };
+            let mut values = values.filter(|value| !(skip_nan && value.is_nan()));
+            values.try_fold(one, Wide::checked_mul)?.into_num_ex()
+        }
+        ReductionOp::Mean => {
+            let mut count = 0;
+            let mut values = values.filter(|value| !(skip_nan && value.is_nan()));
+            let sum = values.try_fold(zero, |acc, value| {
+                count += 1;
+                acc.checked_add(value)
+            })?;
+            if count == 0 {
+                return Err(ArrayError::EmptyReduction.into());
+            }
+            mean(sum, count).into_num_ex()
+        }
+    }
+}
@@ -4,9 +4,10 @@ defmodule OpenCL.ArrayTest do
 
   @integer_types ~w(u8 i8 u16 i16 u32 i32 u64 i64 usize isize)a
   # @integer_types ~w(u8 i8 u16 i16 u32 i32)a # u64 i64 usize isize)a
-  @float_types ~w(f32 f64)a
+  @float_types ~w(f16 f32 f64)a
   # @float_types ~w()a
-  @all_types @integer_types ++ @float_types
+  @complex_types ~w(c32 c64)a
+  @all_types @integer_types ++ @float_types ++ @complex_types
 
   describe "new/1" do
     test "preserves order in conjuction with to_list" do
@@ -24,6 +25,10 @@ defmodule OpenCL.ArrayTest do
     for t <- @float_types do
       ArrayHelpers.test_number_type(t, [1.0, 2.0, 3.0])
     end
+
+    for t <- @complex_types do
+      ArrayHelpers.test_number_type(t, [{1.0, 0.5}, {2.0, -1.0}])
+    end
   end
 
   describe "new/2" do
@@ -34,6 +39,10 @@ defmodule OpenCL.ArrayTest do
     for t <- @float_types do
       ArrayHelpers.test_new(t, [1.0, 2.0, 3.0])
     end
+
+    for t <- @complex_types do
+      ArrayHelpers.test_new(t, [{1.0, 0.5}, {2.0, -1.0}])
+    end
   end
 
   describe "filled_with/1" do
@@ -54,6 +63,10 @@ defmodule OpenCL.ArrayTest do
     for t <- @float_types do
       ArrayHelpers.test_filled_with(t, 2.0, 3)
     end
+
+    for t <- @complex_types do
+      ArrayHelpers.test_filled_with(t, {2.0, 1.0}, 3)
+    end
   end
 
   describe "to_list/1" do
@@ -69,6 +82,329 @@ defmodule OpenCL.ArrayTest do
     for t <- @float_types do
       ArrayHelpers.test_to_list(t, [1.0, 2.0, 3.0])
     end
+
+    for t <- @complex_types do
+      ArrayHelpers.test_to_list(t, [{1.0, 0.5}, {2.0, -1.0}])
+    end
+  end
+
+  describe "from_binary/2" do
+    test "reads raw little-endian numbers" do
+      binary = <<1::little-32, -2::little-signed-32, 3::little-32>>
+      assert {:ok, array} = Array.from_binary(:i32, binary)
+      assert Array.type(array) == :i32
+      assert Array.to_list(array) == [1, -2, 3]
+    end
+
+    test "reads floats and complex numbers" do
+      assert {:ok, array} = Array.from_binary(:f64, <<1.5::little-float-64, -2.0::little-float-64>>)
+      assert Array.to_list(array) == [1.5, -2.0]
+
+      assert {:ok, array} = Array.from_binary(:c32, <<1.0::little-float-32, 2.0::little-float-32>>)
+      assert Array.to_list(array) == [{1.0, 2.0}]
+    end
+
+    test "errors when the size is not a multiple of the number type's size" do
+      assert {:error, _} = Array.from_binary(:u16, <<1, 2, 3>>)
+    end
+  end
+
+  describe "to_binary/1" do
+    test "writes raw little-endian numbers" do
+      array = Array.new(:u16, [1, 256])
+      assert Array.to_binary(array) == <<1, 0, 0, 1>>
+    end
+
+    for t <- @all_types do
+      test "round-trips #{t} arrays through from_binary/2" do
+        t = unquote(t)
+        array = Array.new(t, ArrayHelpers.convert(t, [1, 2, 3]))
+        assert {:ok, copy} = Array.from_binary(t, Array.to_binary(array))
+        assert Array.to_list(copy) == Array.to_list(array)
+      end
+    end
+  end
+
+  describe "to_shared_binary/1" do
+    test "returns the same bytes as to_binary/1" do
+      array = Array.new(:i32, [1, -2, 3])
+      assert Array.to_shared_binary(array) == Array.to_binary(array)
+    end
+
+    test "marks the array as shared" do
+      array = Array.new(:u8, [1, 2, 3])
+      refute Array.shared?(array)
+      _ = Array.to_shared_binary(array)
+      assert Array.shared?(array)
+    end
+
+    test "prevents further modification of the array" do
+      array = Array.new(:u8, [1, 2, 3])
+      binary = Array.to_shared_binary(array)
+      assert {:error, _} = Array.push(array, 4)
+      assert {:error, _} = Array.extend(array, [4, 5])
+      assert binary == <<1, 2, 3>>
+      assert Array.to_list(array) == [1, 2, 3]
+    end
+
+    test "outlives the array term" do
+      binary = Array.new(:u16, [1, 2]) |> Array.to_shared_binary()
+      :erlang.garbage_collect()
+      assert binary == <<1, 0, 2, 0>>
+    end
+  end
+
+  describe "dims/1" do
+    test "is flat for arrays without a shape" do
+      assert Array.dims(Array.new(:u8, [1, 2, 3])) == {3}
+    end
+
+    test "is flattened by push/2" do
+      assert {:ok, array} = Array.reshape(Array.new(:u8, [1, 2, 3, 4]), {2, 2})
+      assert :ok = Array.push(array, 5)
+      assert Array.dims(array) == {5}
+    end
+  end
+
+  describe "reshape/2" do
+    test "sets the shape and strides" do
+      assert {:ok, array} = Array.reshape(Array.new(:i32, Enum.to_list(1..24)), {2, 3, 4})
+      assert Array.dims(array) == {2, 3, 4}
+      assert Array.strides(array) == {12, 4, 1}
+      assert Array.to_list(array) == Enum.to_list(1..24)
+    end
+
+    test "errors when the shape does not match the length" do
+      assert {:error, _} = Array.reshape(Array.new(:i32, [1, 2, 3]), {2, 2})
+    end
+
+    test "errors when the shape overflows" do
+      assert {:error, "ShapeOverflow" <> _} =
+               Array.reshape(Array.new(:i32, []), {4_294_967_296, 4_294_967_296})
+    end
+
+    test "keeps the shape through type_cast/2" do
+      assert {:ok, array} = Array.reshape(Array.new(:i32, [1, 2, 3, 4, 5, 6]), {3, 2})
+      assert Array.dims(Array.type_cast(array, :f32)) == {3, 2}
+    end
+  end
+
+  describe "transpose/2" do
+    test "reverses the axes by default" do
+      assert {:ok, array} = Array.reshape(Array.new(:i32, [1, 2, 3, 4, 5, 6]), {2, 3})
+      assert {:ok, transposed} = Array.transpose(array)
+      assert Array.dims(transposed) == {3, 2}
+      assert Array.to_list(transposed) == [1, 4, 2, 5, 3, 6]
+    end
+
+    test "permutes the axes by the given order" do
+      assert {:ok, array} = Array.reshape(Array.new(:u8, Enum.to_list(0..7)), {2, 2, 2})
+      assert {:ok, permuted} = Array.transpose(array, {0, 2, 1})
+      assert Array.to_list(permuted) == [0, 2, 1, 3, 4, 6, 5, 7]
+    end
+
+    test "errors on invalid axes" do
+      assert {:ok, array} = Array.reshape(Array.new(:u8, [1, 2, 3, 4]), {2, 2})
+      assert {:error, _} = Array.transpose(array, {0, 0})
+      assert {:error, _} = Array.transpose(array, {0, 1, 2})
+    end
+  end
+
+  describe "slice_axis/4" do
+    test "slices rows and columns" do
+      assert {:ok, array} = Array.reshape(Array.new(:i32, [1, 2, 3, 4, 5, 6]), {2, 3})
+      assert {:ok, row} = Array.slice_axis(array, 0, 1, 2)
+      assert Array.dims(row) == {1, 3}
+      assert Array.to_list(row) == [4, 5, 6]
+
+      assert {:ok, cols} = Array.slice_axis(array, 1, 1, 3)
+      assert Array.dims(cols) == {2, 2}
+      assert Array.to_list(cols) == [2, 3, 5, 6]
+    end
+
+    test "errors on an invalid axis or range" do
+      assert {:ok, array} = Array.reshape(Array.new(:i32, [1, 2, 3, 4]), {2, 2})
+      assert {:error, _} = Array.slice_axis(array, 2, 0, 1)
+      assert {:error, _} = Array.slice_axis(array, 0, 1, 3)
+    end
+  end
+
+  describe "element-wise ops" do
+    test "combine two arrays" do
+      left = Array.new(:i32, [1, 2, 3])
+      right = Array.new(:i32, [4, -5, 6])
+      assert {:ok, sum} = Array.add(left, right)
+      assert Array.to_list(sum) == [5, -3, 9]
+      assert {:ok, diff} = Array.sub(left, right)
+      assert Array.to_list(diff) == [-3, 7, -3]
+      assert {:ok, prod} = Array.mul(left, right)
+      assert Array.to_list(prod) == [4, -10, 18]
+      assert {:ok, quot} = Array.div(right, left)
+      assert Array.to_list(quot) == [4, -2, 2]
+      assert {:ok, mins} = Array.min(left, right)
+      assert Array.to_list(mins) == [1, -5, 3]
+      assert {:ok, maxes} = Array.max(left, right)
+      assert Array.to_list(maxes) == [4, 2, 6]
+    end
+
+    test "combine an array and a scalar cast to the array's type" do
+      array = Array.new(:f32, [1.0, 2.0, 3.0])
+      assert {:ok, result} = Array.mul(array, 2)
+      assert Array.type(result) == :f32
+      assert Array.to_list(result) == [2.0, 4.0, 6.0]
+    end
+
+    test "error for a scalar that does not fit the array's type" do
+      assert {:error, "DoesNotFit" <> _} = Array.add(Array.new(:u8, [1, 2]), 300)
+      assert {:error, "DoesNotFit" <> _} = Array.add(Array.new(:u8, [1, 2]), -1)
+      assert {:error, "DoesNotFit" <> _} = Array.mul(Array.new(:i32, [1, 2]), 1.5)
+      assert {:ok, result} = Array.mul(Array.new(:i32, [1, 2]), 2.0)
+      assert Array.to_list(result) == [2, 4]
+    end
+
+    test "combine an array with itself" do
+      array = Array.new(:u8, [1, 2, 3])
+      assert {:ok, result} = Array.add(array, array)
+      assert Array.to_list(result) == [2, 4, 6]
+    end
+
+    test "wrap integers on overflow" do
+      assert {:ok, result} = Array.add(Array.new(:u8, [255]), 1)
+      assert Array.to_list(result) == [0]
+    end
+
+    test "keep the shape of the left array" do
+      assert {:ok, array} = Array.reshape(Array.new(:i32, [1, 2, 3, 4]), {2, 2})
+      assert {:ok, result} = Array.add(array, 1)
+      assert Array.dims(result) == {2, 2}
+    end
+
+    test "work on complex numbers" do
+      array = Array.new(:c64, [{1.0, 1.0}])
+      assert {:ok, result} = Array.mul(array, {0.0, 1.0})
+      assert Array.to_list(result) == [{-1.0, 1.0}]
+      assert {:error, _} = Array.max(array, array)
+    end
+
+    test "error on type or length mismatch" do
+      assert {:error, _} = Array.add(Array.new(:i32, [1, 2]), Array.new(:f32, [1.0, 2.0]))
+      assert {:error, _} = Array.add(Array.new(:i32, [1, 2]), Array.new(:i32, [1]))
+    end
+
+    test "error on integer division by zero" do
+      assert {:error, _} = Array.div(Array.new(:i32, [1, 2]), Array.new(:i32, [1, 0]))
+      assert {:error, _} = Array.div(Array.new(:u8, [1, 2]), 0)
+    end
+  end
+
+  describe "reductions" do
+    test "reduce integers with widening accumulators" do
+      array = Array.filled_with(:u8, 200, 10)
+      assert Array.sum(array) == {:ok, 2000}
+      assert Array.mean(array) == {:ok, 200.0}
+    end
+
+    test "reduce to the extreme values and their indices" do
+      array = Array.new(:i32, [3, -1, 7, -1, 7])
+      assert Array.min(array) == {:ok, -1}
+      assert Array.max(array) == {:ok, 7}
+      assert Array.argmin(array) == {:ok, 1}
+      assert Array.argmax(array) == {:ok, 2}
+      assert Array.product(array) == {:ok, 147}
+    end
+
+    test "reduce floats and complex numbers" do
+      assert Array.sum(Array.new(:f32, [0.5, 1.5, 2.0])) == {:ok, 4.0}
+      assert Array.sum(Array.new(:c32, [{1.0, 2.0}, {3.0, -1.0}])) == {:ok, {4.0, 1.0}}
+      assert {:error, _} = Array.max(Array.new(:c32, [{1.0, 2.0}]))
+    end
+
+    test "error when the result overflows 64 bits" do
+      assert {:error, _} = Array.product(Array.filled_with(:u64, 4_000_000_000, 3))
+    end
+
+    test "error on empty arrays where there is no result" do
+      array = Array.new(:f32, [])
+      assert Array.sum(array) == {:ok, 0.0}
+      assert {:error, _} = Array.mean(array)
+      assert {:error, _} = Array.argmax(array)
+    end
+
+    test "leave NaNs out with the nan_ variants" do
+      assert {:ok, nans} = Array.div(Array.new(:f64, [0.0, 1.0, 3.0]), Array.new(:f64, [0.0, 1.0, 1.0]))
+      assert {:error, _} = Array.sum(nans)
+      assert Array.argmax(nans) == {:ok, 0}
+      assert Array.nan_sum(nans) == {:ok, 4.0}
+      assert Array.nan_mean(nans) == {:ok, 2.0}
+      assert Array.nan_argmax(nans) == {:ok, 2}
+      assert Array.nan_min(nans) == {:ok, 1.0}
+    end
+  end
+
+  describe "get/2 and set/3" do
+    test "read and overwrite single numbers" do
+      array = Array.new(:i16, [1, 2, 3])
+      assert Array.get(array, 1) == {:ok, 2}
+      assert :ok = Array.set(array, 1, 20)
+      assert Array.to_list(array) == [1, 20, 3]
+    end
+
+    test "cast the number to the array's type" do
+      array = Array.new(:f32, [1.0])
+      assert :ok = Array.set(array, 0, 2)
+      assert Array.get(array, 0) == {:ok, 2.0}
+    end
+
+    test "error when the number does not fit the array's type" do
+      array = Array.new(:u8, [1])
+      assert {:error, "DoesNotFit" <> _} = Array.set(array, 0, 300)
+      assert {:error, "DoesNotFit" <> _} = Array.set(Array.new(:i32, [1]), 0, 1.5)
+      assert Array.to_list(array) == [1]
+    end
+
+    test "error when the index is out of bounds" do
+      array = Array.new(:u8, [1, 2, 3])
+      assert {:error, _} = Array.get(array, 3)
+      assert {:error, _} = Array.set(array, 3, 1)
+    end
+  end
+
+  describe "slice/3" do
+    test "copies a range into a new array" do
+      array = Array.new(:u32, [1, 2, 3, 4, 5])
+      assert {:ok, sliced} = Array.slice(array, 1, 3)
+      assert Array.to_list(sliced) == [2, 3, 4]
+      assert :ok = Array.set(sliced, 0, 9)
+      assert Array.to_list(array) == [1, 2, 3, 4, 5]
+    end
+
+    test "errors when the range is out of bounds" do
+      array = Array.new(:u32, [1, 2, 3])
+      assert {:error, _} = Array.slice(array, 2, 2)
+    end
+  end
+
+  describe "put_slice/3" do
+    test "overwrites a range from a list" do
+      array = Array.new(:i64, [1, 2, 3, 4])
+      assert :ok = Array.put_slice(array, 1, [20, 30])
+      assert Array.to_list(array) == [1, 20, 30, 4]
+    end
+
+    test "overwrites a range from an array" do
+      array = Array.new(:i64, [1, 2, 3, 4])
+      assert :ok = Array.put_slice(array, 2, Array.new(:i64, [30, 40]))
+      assert Array.to_list(array) == [1, 2, 30, 40]
+      assert :ok = Array.put_slice(array, 0, array)
+      assert Array.to_list(array) == [1, 2, 30, 40]
+    end
+
+    test "errors when the range is out of bounds or the types differ" do
+      array = Array.new(:i64, [1, 2, 3])
+      assert {:error, _} = Array.put_slice(array, 2, [1, 2])
+      assert {:error, _} = Array.put_slice(array, 0, Array.new(:i32, [1]))
+      assert Array.to_list(array) == [1, 2, 3]
+    end
   end
 
   describe "length/1" do
@@ -85,6 +421,10 @@ defmodule OpenCL.ArrayTest do
     for t <- @float_types do
       ArrayHelpers.test_length(t, [1.0, 2.0, 3.0])
     end
+
+    for t <- @complex_types do
+      ArrayHelpers.test_length(t, [{1.0, 0.5}, {2.0, -1.0}])
+    end
   end
 
 
@@ -96,6 +436,10 @@ defmodule OpenCL.ArrayTest do
     for t <- @float_types do
       ArrayHelpers.test_push(t, [1.0, 2.0, 3.0], 4.0)
     end
+
+    for t <- @complex_types do
+      ArrayHelpers.test_push(t, [{1.0, 0.5}, {2.0, -1.0}], {4.0, 2.0})
+    end
   end
 
   describe "extend/2" do
@@ -106,6 +450,50 @@ defmodule OpenCL.ArrayTest do
     for t <- @float_types do
       ArrayHelpers.test_extend(t, [1.0, 2.0, 3.0], [4.0, 5.0])
     end
+
+    for t <- @complex_types do
+      ArrayHelpers.test_extend(t, [{1.0, 0.5}, {2.0, -1.0}], [{4.0, 0.0}])
+    end
+  end
+
+  describe "type_cast/3" do
+    test ":wrapping wraps integers around" do
+      assert {:ok, array} = Array.type_cast(Array.new(:i32, [300, -1]), :u8, :wrapping)
+      assert Array.to_list(array) == [44, 255]
+    end
+
+    test ":saturating clamps to the target type's range" do
+      assert {:ok, array} = Array.type_cast(Array.new(:i32, [300, -1, 7]), :u8, :saturating)
+      assert Array.to_list(array) == [255, 0, 7]
+
+      assert {:ok, array} = Array.type_cast(Array.new(:f64, [1.0e10, -1.0e10]), :i16, :saturating)
+      assert Array.to_list(array) == [32767, -32768]
+    end
+
+    test ":checked errors on the first number that doesn't fit" do
+      assert {:error, reason} = Array.type_cast(Array.new(:i32, [1, 300, 400]), :u8, :checked)
+      assert reason =~ "CastFailed(1, \"I32(300)\", U8)"
+
+      assert {:ok, array} = Array.type_cast(Array.new(:i32, [1, 255]), :u8, :checked)
+      assert Array.to_list(array) == [1, 255]
+    end
+
+    test ":saturating and :checked respect the range of :f16" do
+      array = Array.new(:f32, [70000.0, -70000.0, 1.5])
+      assert {:ok, saturated} = Array.type_cast(array, :f16, :saturating)
+      assert Array.to_list(saturated) == [65504.0, -65504.0, 1.5]
+      assert {:error, reason} = Array.type_cast(array, :f16, :checked)
+      assert reason =~ "CastFailed(0, "
+
+      assert {:ok, array} = Array.type_cast(Array.new(:f32, [65504.0, -2.5]), :f16, :checked)
+      assert Array.to_list(array) == [65504.0, -2.5]
+    end
+
+    test ":checked rejects complex numbers with an imaginary part" do
+      array = Array.new(:c32, [{1.0, 0.0}, {1.0, 1.0}])
+      assert {:error, _} = Array.type_cast(array, :f32, :checked)
+      assert {:ok, _} = Array.type_cast(array, :c64, :checked)
+    end
   end
 
   describe "type_cast/2" do
@@ -0,0 +1,54 @@
+defmodule OpenCL.BufferMapTest do
+  use ExUnit.Case
+  use OpenCL.SessionsCase
+
+  alias OpenCL.Array
+  alias OpenCL.BufferMap
+  alias OpenCL.Session
+
+  describe "map_buffer/5" do
+    test "maps a range of the buffer", %{sessions: sessions} do
+      for session <- sessions do
+        assert {:ok, buffer} = Session.create_buffer(session, :i32, Array.new(:i32, [1, 2, 3, 4]))
+        assert {:ok, buffer_map} = Session.map_buffer(session, buffer, :read, 1, 2)
+        assert BufferMap.mapped?(buffer_map)
+        assert BufferMap.length(buffer_map) == 2
+        assert BufferMap.number_type(buffer_map) == :i32
+        assert {:ok, array} = BufferMap.to_array(buffer_map)
+        assert Array.to_list(array) == [2, 3]
+        assert :ok = BufferMap.unmap(buffer_map)
+        refute BufferMap.mapped?(buffer_map)
+        assert {:error, _} = BufferMap.to_array(buffer_map)
+      end
+    end
+
+    test "writes are visible once unmapped", %{sessions: sessions} do
+      for session <- sessions do
+        assert {:ok, buffer} = Session.create_buffer(session, :i32, Array.new(:i32, [1, 2, 3, 4]))
+        assert {:ok, buffer_map} = Session.map_buffer(session, buffer, :write, 0, 4)
+        assert :ok = BufferMap.write_array(buffer_map, 2, Array.new(:i32, [30, 40]))
+        assert {:error, _} = BufferMap.to_array(buffer_map)
+        assert :ok = BufferMap.unmap(buffer_map)
+        assert {:ok, array} = Session.read_buffer(session, buffer)
+        assert Array.to_list(array) == [1, 2, 30, 40]
+      end
+    end
+
+    test "a shared binary can no longer be unmapped", %{sessions: sessions} do
+      for session <- sessions do
+        assert {:ok, buffer} = Session.create_buffer(session, :u8, Array.new(:u8, [1, 2, 3]))
+        assert {:ok, buffer_map} = Session.map_buffer(session, buffer, :read_write, 0, 3)
+        assert {:ok, <<1, 2, 3>>} = BufferMap.to_shared_binary(buffer_map)
+        assert {:error, _} = BufferMap.write_array(buffer_map, 0, Array.new(:u8, [9]))
+        assert {:error, _} = BufferMap.unmap(buffer_map)
+      end
+    end
+
+    test "errors when the range is out of bounds", %{sessions: sessions} do
+      for session <- sessions do
+        assert {:ok, buffer} = Session.create_buffer(session, :u8, 3)
+        assert {:error, _} = Session.map_buffer(session, buffer, :read, 2, 2)
+      end
+    end
+  end
+end
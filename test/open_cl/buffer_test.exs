@@ -1,5 +1,6 @@
 defmodule OpenCL.BufferTest do
   use ExUnit.Case
+  alias OpenCL.Array
   alias OpenCL.Buffer
   alias OpenCL.Session
 
@@ -22,4 +23,29 @@ defmodule OpenCL.BufferTest do
       end
     end
   end
+
+  describe "sub_buffer/3" do
+    test "shares memory with the parent buffer", %{sessions: sessions} do
+      for session <- sessions do
+        data = Array.new(:u8, Enum.map(0..255, fn _ -> 0 end))
+        assert {:ok, parent} = Session.create_buffer(session, :u8, data)
+        assert {:ok, sub} = Buffer.sub_buffer(parent, 128, 3)
+        assert Buffer.sub_buffer?(sub)
+        refute Buffer.sub_buffer?(parent)
+        assert Buffer.length(sub) == {:ok, 3}
+        assert Buffer.number_type(sub) == :u8
+
+        assert :ok = Session.write_buffer(session, sub, Array.new(:u8, [1, 2, 3]))
+        assert {:ok, array} = Session.read_buffer(session, parent)
+        assert array |> Array.to_list() |> Enum.slice(127, 5) == [0, 1, 2, 3, 0]
+      end
+    end
+
+    test "errors when the range is out of bounds", %{sessions: sessions} do
+      for session <- sessions do
+        assert {:ok, parent} = Session.create_buffer(session, :u8, 10)
+        assert {:error, _} = Buffer.sub_buffer(parent, 8, 3)
+      end
+    end
+  end
 end
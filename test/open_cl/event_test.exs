@@ -0,0 +1,42 @@
+defmodule OpenCL.EventTest do
+  use ExUnit.Case, async: true
+  use OpenCL.SessionsCase
+
+  alias OpenCL.Array
+  alias OpenCL.Event
+  alias OpenCL.Session
+
+  describe "Session.execute_kernel_async/5" do
+    test "returns an event that can be waited on", %{sessions: sessions} do
+      for session <- sessions do
+        array = Array.new(:u8, [1, 2, 3])
+        assert {:ok, buffer} = Session.create_buffer(session, :u8, array)
+        assert {:ok, %Event{} = event} = Session.execute_kernel_async(session, "add_one_u8", 3, [buffer])
+        assert :ok = Event.wait(event)
+        assert {:ok, :complete} = Event.status(event)
+        assert {:ok, array2} = Session.read_buffer(session, buffer)
+        assert Array.to_list(array2) == [2, 3, 4]
+      end
+    end
+
+    test "returns an error for an unknown kernel", %{sessions: sessions} do
+      for session <- sessions do
+        assert {:ok, buffer} = Session.create_buffer(session, :u8, 3)
+        assert {:error, _} = Session.execute_kernel_async(session, "not_a_kernel", 3, [buffer])
+      end
+    end
+  end
+
+  describe "on_complete/3" do
+    test "sends the message when the kernel is done", %{sessions: sessions} do
+      for session <- sessions do
+        assert {:ok, buffer} = Session.create_buffer(session, :u8, Array.filled_with(:u8, 0, 100))
+        assert {:ok, event} = Session.execute_kernel_async(session, "add_one_u8", 100, [buffer])
+        ref = make_ref()
+        assert :ok = Event.on_complete(event, self(), {:done, ref})
+        assert_receive {:done, ^ref}, 5_000
+        assert {:ok, :complete} = Event.status(event)
+      end
+    end
+  end
+end
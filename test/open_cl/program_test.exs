@@ -0,0 +1,139 @@
+defmodule OpenCL.ProgramTest do
+  use ExUnit.Case, async: true
+  use OpenCL.SessionsCase
+
+  alias OpenCL.Array
+  alias OpenCL.BuildError
+  alias OpenCL.Kernel
+  alias OpenCL.Program
+  alias OpenCL.Session
+  alias OpenCL.SourceHelpers
+
+  @scale_src """
+  __kernel void scale_f32(__global float *data, float factor) {
+      data[get_global_id(0)] *= factor;
+  }
+  """
+
+  describe "create/2" do
+    test "lists the kernel names of the program", %{sessions: sessions} do
+      for session <- sessions do
+        assert {:ok, program} = Program.create(session, SourceHelpers.full())
+        assert {:ok, names} = Program.kernel_names(program)
+        assert Enum.sort(names) == ["add_one_i32", "add_one_u8"]
+      end
+    end
+  end
+
+  @typo_src """
+  __kernel void typo(__global float *data) {
+      data[get_global_id(0)] = undeclared;
+  }
+  """
+
+  describe "create/3 with :cache_dir" do
+    setup do
+      dir = Path.join(System.tmp_dir!(), "open_cl_program_cache_#{System.unique_integer([:positive])}")
+      on_exit(fn -> File.rm_rf!(dir) end)
+      {:ok, cache_dir: dir}
+    end
+
+    test "stores the binary and loads it on the next create", %{sessions: sessions, cache_dir: dir} do
+      for session <- sessions do
+        assert {:ok, _} = Program.create(session, @scale_src, cache_dir: dir)
+        assert [bin] = Path.wildcard(Path.join(dir, "*.bin"))
+        assert File.stat!(bin).size > 0
+
+        assert {:ok, program} = Program.create(session, @scale_src, cache_dir: dir)
+        assert {:ok, ["scale_f32"]} = Program.kernel_names(program)
+        assert [^bin] = Path.wildcard(Path.join(dir, "*.bin"))
+      end
+    end
+
+    test "keeps the kernel arg info of a cached program", %{sessions: sessions, cache_dir: dir} do
+      for session <- sessions do
+        assert {:ok, _} = Program.create(session, @scale_src, cache_dir: dir)
+        assert {:ok, program} = Program.create(session, @scale_src, cache_dir: dir)
+        assert {:ok, kernel} = Program.create_kernel(program, "scale_f32")
+        assert {:ok, [_data, factor]} = Kernel.arg_info(kernel)
+        assert %{name: "factor", type_name: "float"} = factor
+      end
+    end
+
+    test "keys the cache by build options", %{sessions: sessions, cache_dir: dir} do
+      for session <- sessions do
+        assert {:ok, _} = Program.create(session, @scale_src, cache_dir: dir)
+        opts = [cache_dir: dir, build_options: [defines: %{"UNUSED" => 1}]]
+        assert {:ok, _} = Program.create(session, @scale_src, opts)
+        assert length(Path.wildcard(Path.join(dir, "*.bin"))) == 2
+      end
+    end
+
+    test "falls back to compiling when the cached binary is corrupt", %{sessions: sessions, cache_dir: dir} do
+      for session <- sessions do
+        assert {:ok, _} = Program.create(session, @scale_src, cache_dir: dir)
+        [bin] = Path.wildcard(Path.join(dir, "*.bin"))
+        File.write!(bin, "not a program binary")
+        assert {:ok, program} = Program.create(session, @scale_src, cache_dir: dir)
+        assert {:ok, ["scale_f32"]} = Program.kernel_names(program)
+      end
+    end
+  end
+
+  describe "build errors" do
+    test "Program.create/2 returns a BuildError with diagnostics", %{sessions: sessions} do
+      for session <- sessions do
+        assert {:error, %BuildError{} = err} = Program.create(session, @typo_src)
+        assert err.status_code == -11
+        assert [%{line: 2, message: message} | _] = BuildError.diagnostics(err)
+        assert message =~ "undeclared"
+      end
+    end
+
+    test "Session.create/1 returns a BuildError with per-device logs" do
+      assert {:error, %BuildError{logs: [_ | _] = logs}} = Session.create(@typo_src)
+      for %{device_name: device_name, log: log} <- logs do
+        assert is_binary(device_name)
+        assert log =~ "undeclared"
+      end
+    end
+  end
+
+  describe "Kernel" do
+    test "exposes the name and arg info", %{sessions: sessions} do
+      for session <- sessions do
+        assert {:ok, program} = Program.create(session, @scale_src)
+        assert {:ok, kernel} = Program.create_kernel(program, "scale_f32")
+        assert {:ok, "scale_f32"} = Kernel.name(kernel)
+        assert {:ok, 2} = Kernel.num_args(kernel)
+        assert {:ok, [data, factor]} = Kernel.arg_info(kernel)
+        assert %{name: "data", type_name: "float*", address_qualifier: :global} = data
+        assert %{name: "factor", type_name: "float", address_qualifier: :private} = factor
+      end
+    end
+
+    test "can be launched many times with args bound once", %{sessions: sessions} do
+      for session <- sessions do
+        assert {:ok, program} = Program.create(session, SourceHelpers.full())
+        assert {:ok, kernel} = Program.create_kernel(program, "add_one_u8")
+        assert {:ok, buffer} = Session.create_buffer(session, :u8, Array.new(:u8, [0, 1, 2]))
+        assert :ok = Kernel.set_args(kernel, [buffer])
+
+        for _ <- 1..3 do
+          assert :ok = Session.launch_kernel(session, kernel, 3)
+        end
+
+        assert {:ok, array} = Session.read_buffer(session, buffer)
+        assert Array.to_list(array) == [3, 4, 5]
+      end
+    end
+
+    test "cannot be launched with unset args", %{sessions: sessions} do
+      for session <- sessions do
+        assert {:ok, program} = Program.create(session, SourceHelpers.full())
+        assert {:ok, kernel} = Program.create_kernel(program, "add_one_u8")
+        assert {:error, _} = Session.launch_kernel(session, kernel, 3)
+      end
+    end
+  end
+end
@@ -4,6 +4,7 @@ defmodule OpenCL.SessionTest do
 
   alias OpenCL.Session
   alias OpenCL.Array
+  alias OpenCL.ProfilingInfo
 
   test "session concurrency", %{sessions: sessions} do
      1..10
@@ -22,4 +23,510 @@ defmodule OpenCL.SessionTest do
       |> Enum.map(fn task -> Task.await(task) end)
   end
 
+  describe "execute_kernel_notify/5" do
+    test "sends :opencl_complete when the kernel is done", %{sessions: sessions} do
+      for session <- sessions do
+        assert {:ok, buffer} = Session.create_buffer(session, :u8, Array.new(:u8, [1, 2, 3]))
+        assert {:ok, ref} = Session.execute_kernel_notify(session, "add_one_u8", 3, [buffer])
+        assert_receive {:opencl_complete, ^ref, :ok}, 5_000
+        assert {:ok, array} = Session.read_buffer(session, buffer)
+        assert Array.to_list(array) == [2, 3, 4]
+      end
+    end
+  end
+
+  describe "read_buffer_into/3" do
+    test "reads into an array of the same length", %{sessions: sessions} do
+      for session <- sessions do
+        assert {:ok, buffer} = Session.create_buffer(session, :i32, Array.new(:i32, [1, 2, 3]))
+        array = Array.filled_with(:i32, 0, 3)
+        assert :ok = Session.read_buffer_into(session, buffer, array)
+        assert Array.to_list(array) == [1, 2, 3]
+      end
+    end
+
+    test "resizes an array of a different length", %{sessions: sessions} do
+      for session <- sessions do
+        assert {:ok, buffer} = Session.create_buffer(session, :i32, Array.new(:i32, [1, 2, 3]))
+        array = Array.new(:i32, [])
+        assert :ok = Session.read_buffer_into(session, buffer, array)
+        assert Array.to_list(array) == [1, 2, 3]
+      end
+    end
+
+    test "errors for a mismatched number type", %{sessions: sessions} do
+      for session <- sessions do
+        assert {:ok, buffer} = Session.create_buffer(session, :i32, 3)
+        assert {:error, _} = Session.read_buffer_into(session, buffer, Array.filled_with(:u8, 0, 3))
+      end
+    end
+  end
+
+  describe "read_buffer_range/4 and write_buffer_range/4" do
+    test "transfer only the requested range", %{sessions: sessions} do
+      for session <- sessions do
+        assert {:ok, buffer} = Session.create_buffer(session, :i32, Array.new(:i32, [1, 2, 3, 4, 5]))
+        assert {:ok, array} = Session.read_buffer_range(session, buffer, 1, 3)
+        assert Array.to_list(array) == [2, 3, 4]
+
+        assert :ok = Session.write_buffer_range(session, buffer, 3, Array.new(:i32, [40, 50]))
+        assert {:ok, array} = Session.read_buffer(session, buffer)
+        assert Array.to_list(array) == [1, 2, 3, 40, 50]
+      end
+    end
+
+    test "error when the range is out of bounds", %{sessions: sessions} do
+      for session <- sessions do
+        assert {:ok, buffer} = Session.create_buffer(session, :i32, 5)
+        assert {:error, _} = Session.read_buffer_range(session, buffer, 4, 2)
+        assert {:error, _} = Session.write_buffer_range(session, buffer, 4, Array.new(:i32, [1, 2]))
+      end
+    end
+
+    test "read an empty range without enqueuing", %{sessions: sessions} do
+      for session <- sessions do
+        assert {:ok, buffer} = Session.create_buffer(session, :i32, 5)
+        assert {:ok, array} = Session.read_buffer_range(session, buffer, 5, 0)
+        assert Array.to_list(array) == []
+        assert {:ok, array} = Session.read_buffer(session, buffer, offset: 5)
+        assert Array.to_list(array) == []
+        assert {:error, _} = Session.read_buffer_range(session, buffer, 6, 0)
+      end
+    end
+
+    test "write an empty array without enqueuing", %{sessions: sessions} do
+      for session <- sessions do
+        assert {:ok, buffer} = Session.create_buffer(session, :i32, Array.new(:i32, [1, 2, 3]))
+        assert :ok = Session.write_buffer_range(session, buffer, 3, Array.new(:i32, []))
+        assert :ok = Session.write_buffer(session, buffer, Array.new(:i32, []))
+        assert {:error, _} = Session.write_buffer_range(session, buffer, 4, Array.new(:i32, []))
+        assert {:ok, array} = Session.read_buffer(session, buffer)
+        assert Array.to_list(array) == [1, 2, 3]
+      end
+    end
+  end
+
+  describe "buffer rects" do
+    # a 4x3 grid of u8s stored row by row.
+    @grid Enum.to_list(0..11)
+
+    test "read_buffer_rect/5 reads a 2x2 block", %{sessions: sessions} do
+      for session <- sessions do
+        assert {:ok, buffer} = Session.create_buffer(session, :u8, Array.new(:u8, @grid))
+        assert {:ok, array} = Session.read_buffer_rect(session, buffer, {1, 1}, {2, 2}, row_pitch: 4)
+        assert Array.to_list(array) == [5, 6, 9, 10]
+      end
+    end
+
+    test "write_buffer_rect/6 writes a 2x2 block", %{sessions: sessions} do
+      for session <- sessions do
+        assert {:ok, buffer} = Session.create_buffer(session, :u8, Array.new(:u8, @grid))
+        block = Array.new(:u8, [100, 101, 102, 103])
+        assert :ok = Session.write_buffer_rect(session, buffer, {2, 0}, {2, 2}, block, row_pitch: 4)
+        assert {:ok, array} = Session.read_buffer(session, buffer)
+        assert Array.to_list(array) == [0, 1, 100, 101, 4, 5, 102, 103, 8, 9, 10, 11]
+      end
+    end
+
+    test "write_buffer_rect/6 errors when the array does not fill the region",
+         %{sessions: sessions} do
+      for session <- sessions do
+        assert {:ok, buffer} = Session.create_buffer(session, :u8, Array.new(:u8, @grid))
+        block = Array.new(:u8, [1, 2, 3])
+        assert {:error, _} = Session.write_buffer_rect(session, buffer, {0, 0}, {2, 2}, block, row_pitch: 4)
+      end
+    end
+
+    test "error when the rect does not fit in the buffer", %{sessions: sessions} do
+      for session <- sessions do
+        assert {:ok, buffer} = Session.create_buffer(session, :u8, Array.new(:u8, @grid))
+
+        assert {:error, "RectOutOfBounds" <> _} =
+                 Session.read_buffer_rect(session, buffer, {3, 2}, {2, 2}, row_pitch: 4)
+
+        assert {:error, "RectOutOfBounds" <> _} =
+                 Session.read_buffer_rect(session, buffer, {0, 0}, {4, 2}, row_pitch: 2)
+
+        huge = 4_294_967_296
+
+        assert {:error, "RectOutOfBounds" <> _} =
+                 Session.read_buffer_rect(session, buffer, {0, 0, 0}, {huge, huge, huge})
+
+        assert {:ok, dst} = Session.create_buffer(session, :u8, 3)
+
+        assert {:error, "RectOutOfBounds" <> _} =
+                 Session.copy_buffer_rect(session, buffer, dst, {0, 0}, {0, 0}, {2, 2},
+                   src_row_pitch: 4
+                 )
+      end
+    end
+
+    test "copy_buffer_rect/7 copies a block between buffers", %{sessions: sessions} do
+      for session <- sessions do
+        assert {:ok, src} = Session.create_buffer(session, :u8, Array.new(:u8, @grid))
+        assert {:ok, dst} = Session.create_buffer(session, :u8, Array.filled_with(:u8, 0, 4))
+
+        assert :ok =
+                 Session.copy_buffer_rect(session, src, dst, {1, 1}, {0, 0}, {2, 2},
+                   src_row_pitch: 4,
+                   dst_row_pitch: 2
+                 )
+
+        assert {:ok, array} = Session.read_buffer(session, dst)
+        assert Array.to_list(array) == [5, 6, 9, 10]
+      end
+    end
+  end
+
+  describe "copy_buffer/6" do
+    test "copies a range between buffers", %{sessions: sessions} do
+      for session <- sessions do
+        assert {:ok, src} = Session.create_buffer(session, :i32, Array.new(:i32, [1, 2, 3, 4]))
+        assert {:ok, dst} = Session.create_buffer(session, :i32, Array.filled_with(:i32, 0, 4))
+        assert :ok = Session.copy_buffer(session, src, dst, 1, 2, 2)
+        assert {:ok, array} = Session.read_buffer(session, dst)
+        assert Array.to_list(array) == [0, 0, 2, 3]
+      end
+    end
+
+    test "errors for mismatched number types", %{sessions: sessions} do
+      for session <- sessions do
+        assert {:ok, src} = Session.create_buffer(session, :i32, 4)
+        assert {:ok, dst} = Session.create_buffer(session, :u8, 4)
+        assert {:error, _} = Session.copy_buffer(session, src, dst, 0, 0, 4)
+      end
+    end
+
+    test "copies nothing for a length of 0", %{sessions: sessions} do
+      for session <- sessions do
+        assert {:ok, src} = Session.create_buffer(session, :i32, Array.new(:i32, [1, 2]))
+        assert {:ok, dst} = Session.create_buffer(session, :i32, Array.new(:i32, [3, 4]))
+        assert :ok = Session.copy_buffer(session, src, dst, 2, 0, 0)
+        assert {:ok, array} = Session.read_buffer(session, dst)
+        assert Array.to_list(array) == [3, 4]
+      end
+    end
+  end
+
+  describe "fill_buffer/5" do
+    test "fills a range with a number", %{sessions: sessions} do
+      for session <- sessions do
+        assert {:ok, buffer} = Session.create_buffer(session, :f32, 5)
+        assert :ok = Session.fill_buffer(session, buffer, 0.0, 0, 5)
+        assert :ok = Session.fill_buffer(session, buffer, 1.5, 1, 3)
+        assert {:ok, array} = Session.read_buffer(session, buffer)
+        assert Array.to_list(array) == [0.0, 1.5, 1.5, 1.5, 0.0]
+      end
+    end
+
+    test "errors when the range is out of bounds", %{sessions: sessions} do
+      for session <- sessions do
+        assert {:ok, buffer} = Session.create_buffer(session, :f32, 5)
+        assert {:error, _} = Session.fill_buffer(session, buffer, 1.0, 3, 3)
+      end
+    end
+
+    test "fills nothing for a length of 0", %{sessions: sessions} do
+      for session <- sessions do
+        assert {:ok, buffer} = Session.create_buffer(session, :f32, Array.new(:f32, [1.0, 2.0]))
+        assert :ok = Session.fill_buffer(session, buffer, 0.0, 2, 0)
+        assert {:ok, array} = Session.read_buffer(session, buffer)
+        assert Array.to_list(array) == [1.0, 2.0]
+      end
+    end
+
+    test "errors when the number does not fit the buffer's type", %{sessions: sessions} do
+      for session <- sessions do
+        assert {:ok, u8_buffer} = Session.create_buffer(session, :u8, 5)
+        assert {:error, "DoesNotFit" <> _} = Session.fill_buffer(session, u8_buffer, -1, 0, 5)
+        assert {:ok, i32_buffer} = Session.create_buffer(session, :i32, 5)
+        assert {:error, "DoesNotFit" <> _} = Session.fill_buffer(session, i32_buffer, 1.5, 0, 5)
+      end
+    end
+  end
+
+  describe "read_buffer_notify/3" do
+    test "sends the read array with :opencl_complete", %{sessions: sessions} do
+      for session <- sessions do
+        assert {:ok, buffer} = Session.create_buffer(session, :u8, Array.new(:u8, [1, 2, 3]))
+        ref = make_ref()
+        assert {:ok, ^ref} = Session.read_buffer_notify(session, buffer, ref: ref)
+        assert_receive {:opencl_complete, ^ref, {:ok, %Array{} = array}}, 5_000
+        assert Array.to_list(array) == [1, 2, 3]
+      end
+    end
+  end
+
+  describe "create/2 with :build_options" do
+    @tiled_src """
+    __kernel void add_tile_u8(__global uchar *nums) {
+        nums[get_global_id(0)] += TILE * SCALE;
+    }
+    """
+
+    test "passes defines to the compiler" do
+      opts = [build_options: [defines: %{"TILE" => 2, SCALE: 3}, cl_std: "CL1.2"]]
+      assert {:ok, [session | _]} = Session.create(@tiled_src, opts)
+      assert {:ok, buffer} = Session.create_buffer(session, :u8, Array.new(:u8, [1, 2, 3]))
+      assert :ok = Session.execute_kernel(session, "add_tile_u8", 3, [buffer])
+      assert {:ok, array} = Session.read_buffer(session, buffer)
+      assert Array.to_list(array) == [7, 8, 9]
+    end
+
+    test "fails to build without the defines" do
+      assert {:error, %OpenCL.BuildError{options: ""}} = Session.create(@tiled_src)
+    end
+
+    test "escapes quotes and backslashes in define values" do
+      opts = [build_options: [defines: %{"TILE" => ~s("\\x02"[0]), SCALE: 3}]]
+      assert {:ok, [session | _]} = Session.create(@tiled_src, opts)
+      assert {:ok, buffer} = Session.create_buffer(session, :u8, Array.new(:u8, [1, 2, 3]))
+      assert :ok = Session.execute_kernel(session, "add_tile_u8", 3, [buffer])
+      assert {:ok, array} = Session.read_buffer(session, buffer)
+      assert Array.to_list(array) == [7, 8, 9]
+    end
+
+    test "errors when an option contains a NUL byte" do
+      opts = [build_options: [defines: %{"TILE" => "2\0", SCALE: 3}]]
+      assert {:error, "InvalidOptions" <> _} = Session.create(@tiled_src, opts)
+    end
+  end
+
+  describe "create/2 with :cache_dir" do
+    setup do
+      dir = Path.join(System.tmp_dir!(), "open_cl_session_cache_#{System.unique_integer([:positive])}")
+      on_exit(fn -> File.rm_rf!(dir) end)
+      {:ok, cache_dir: dir}
+    end
+
+    test "loads the program binaries from the cache on the next create", %{cache_dir: dir} do
+      src = OpenCL.SourceHelpers.full()
+      assert {:ok, _} = Session.create(src, cache_dir: dir)
+      bins = Path.wildcard(Path.join(dir, "*.bin"))
+      assert bins != []
+      # a store replaces the file, so an unchanged inode means it was only loaded.
+      inodes = Enum.map(bins, fn bin -> File.stat!(bin).inode end)
+
+      assert {:ok, [session | _]} = Session.create(src, cache_dir: dir)
+      assert Path.wildcard(Path.join(dir, "*.bin")) == bins
+      assert Enum.map(bins, fn bin -> File.stat!(bin).inode end) == inodes
+
+      assert {:ok, buffer} = Session.create_buffer(session, :u8, Array.new(:u8, [1, 2, 3]))
+      assert :ok = Session.execute_kernel(session, "add_one_u8", 3, [buffer])
+      assert {:ok, array} = Session.read_buffer(session, buffer)
+      assert Array.to_list(array) == [2, 3, 4]
+    end
+  end
+
+  describe "profiled functions" do
+    setup do
+      {:ok, [session | _]} =
+        Session.create(OpenCL.SourceHelpers.full(), command_queue_properties: [:profiling_enabled])
+      {:ok, profiled_session: session}
+    end
+
+    test "return device timestamps", %{profiled_session: session} do
+      assert {:ok, buffer} = Session.create_buffer(session, :u8, 3)
+      assert {:ok, %ProfilingInfo{} = write} =
+               Session.write_buffer_profiled(session, buffer, Array.new(:u8, [1, 2, 3]))
+      assert {:ok, %ProfilingInfo{} = exec} =
+               Session.execute_kernel_profiled(session, "add_one_u8", 3, [buffer])
+      assert {:ok, array, %ProfilingInfo{} = read} = Session.read_buffer_profiled(session, buffer)
+      assert Array.to_list(array) == [2, 3, 4]
+
+      for info <- [write, exec, read] do
+        assert info.queued <= info.submitted
+        assert info.submitted <= info.start
+        assert info.start <= info.end
+        assert ProfilingInfo.duration(info) >= 0
+      end
+    end
+
+    test "transfer at the :offset", %{profiled_session: session} do
+      assert {:ok, buffer} = Session.create_buffer(session, :u8, Array.new(:u8, [0, 0, 0, 0]))
+      assert {:ok, %ProfilingInfo{}} =
+               Session.write_buffer_profiled(session, buffer, Array.new(:u8, [1, 2]), offset: 2)
+      assert {:ok, array, %ProfilingInfo{}} = Session.read_buffer_profiled(session, buffer, offset: 1)
+      assert Array.to_list(array) == [0, 1, 2]
+    end
+
+    test "write errors when the array does not fit", %{profiled_session: session} do
+      assert {:ok, buffer} = Session.create_buffer(session, :u8, 3)
+      assert {:error, _} =
+               Session.write_buffer_profiled(session, buffer, Array.new(:u8, [1, 2, 3, 4]))
+    end
+
+    test "fail without :profiling_enabled", %{sessions: [session | _]} do
+      assert {:ok, buffer} = Session.create_buffer(session, :u8, 3)
+      assert {:error, %OpenCL.StatusCodeError{status_code: -7}} =
+               Session.read_buffer_profiled(session, buffer)
+    end
+  end
+
+  describe "execute_kernel/5 with mixed arg types" do
+    @mixed_src """
+    __kernel void scale_by_i32(__global float *data, int factor, uchar offset) {
+        data[get_global_id(0)] = data[get_global_id(0)] * factor + offset;
+    }
+    """
+
+    test "binds a float buffer with typed scalars" do
+      assert {:ok, [session | _]} = Session.create(@mixed_src)
+      assert {:ok, buffer} = Session.create_buffer(session, :f32, Array.new(:f32, [1.0, 2.0]))
+      args = [buffer, {:i32, 3}, {:u8, 1}]
+      assert {:ok, ^buffer} = Session.execute_kernel(session, "scale_by_i32", 2, args, returning: 0)
+      assert {:ok, array} = Session.read_buffer(session, buffer)
+      assert Array.to_list(array) == [4.0, 7.0]
+    end
+
+    test "rejects a tagged scalar with an unknown type" do
+      assert {:ok, [session | _]} = Session.create(@mixed_src)
+      assert {:ok, buffer} = Session.create_buffer(session, :f32, 2)
+      assert {:error, [args: "invalid arg at index 1"]} =
+               Session.execute_kernel(session, "scale_by_i32", 2, [buffer, {:int, 3}, {:u8, 1}])
+    end
+
+    test "casts untagged scalars to the kernel's arg types" do
+      assert {:ok, [session | _]} = Session.create(@mixed_src)
+      assert {:ok, buffer} = Session.create_buffer(session, :f32, Array.new(:f32, [1.0, 2.0]))
+      assert :ok = Session.execute_kernel(session, "scale_by_i32", 2, [buffer, 3, 1])
+      assert {:ok, array} = Session.read_buffer(session, buffer)
+      assert Array.to_list(array) == [4.0, 7.0]
+    end
+
+    test "rejects scalars that do not fit the arg type" do
+      assert {:ok, [session | _]} = Session.create(@mixed_src)
+      assert {:ok, buffer} = Session.create_buffer(session, :f32, 2)
+
+      for args <- [[buffer, 1.5, 1], [buffer, 3, 300], [buffer, 3, {:u8, -1}]] do
+        assert {:error, "DoesNotFit" <> _} = Session.execute_kernel(session, "scale_by_i32", 2, args)
+      end
+    end
+
+    test "casts untagged scalars for a program loaded from the cache" do
+      dir = Path.join(System.tmp_dir!(), "open_cl_mixed_cache_#{System.unique_integer([:positive])}")
+      on_exit(fn -> File.rm_rf!(dir) end)
+      assert {:ok, _} = Session.create(@mixed_src, cache_dir: dir)
+      assert [_ | _] = Path.wildcard(Path.join(dir, "*.bin"))
+
+      assert {:ok, [session | _]} = Session.create(@mixed_src, cache_dir: dir)
+      assert {:ok, buffer} = Session.create_buffer(session, :f32, Array.new(:f32, [1.0, 2.0]))
+      assert :ok = Session.execute_kernel(session, "scale_by_i32", 2, [buffer, 3, 1])
+      assert {:ok, array} = Session.read_buffer(session, buffer)
+      assert Array.to_list(array) == [4.0, 7.0]
+    end
+
+    test "runs with is_blocking: false" do
+      assert {:ok, [session | _]} = Session.create(@mixed_src)
+      assert {:ok, buffer} = Session.create_buffer(session, :f32, Array.new(:f32, [1.0, 2.0]))
+      args = [buffer, 3, 1]
+      assert :ok = Session.execute_kernel(session, "scale_by_i32", 2, args, is_blocking: false)
+      assert {:ok, array} = Session.read_buffer(session, buffer)
+      assert Array.to_list(array) == [4.0, 7.0]
+    end
+  end
+
+  describe "execute_kernel/5 with local args" do
+    @local_sum_src """
+    __kernel void group_sum(__global int *data, __global int *sums, __local int *scratch) {
+        size_t lid = get_local_id(0);
+        scratch[lid] = data[get_global_id(0)];
+        barrier(CLK_LOCAL_MEM_FENCE);
+        for (size_t stride = get_local_size(0) / 2; stride > 0; stride /= 2) {
+            if (lid < stride) {
+                scratch[lid] += scratch[lid + stride];
+            }
+            barrier(CLK_LOCAL_MEM_FENCE);
+        }
+        if (lid == 0) {
+            sums[get_group_id(0)] = scratch[0];
+        }
+    }
+    """
+
+    test "allocates __local scratch memory per work group" do
+      assert {:ok, [session | _]} = Session.create(@local_sum_src)
+      assert {:ok, data} = Session.create_buffer(session, :i32, Array.new(:i32, Enum.to_list(1..8)))
+      assert {:ok, sums} = Session.create_buffer(session, :i32, 2)
+      work = [global_work_size: 8, local_work_size: 4]
+      args = [data, sums, {:local, :i32, 4}]
+      assert {:ok, ^sums} = Session.execute_kernel(session, "group_sum", work, args, returning: 1)
+      assert {:ok, array} = Session.read_buffer(session, sums)
+      assert Array.to_list(array) == [10, 26]
+    end
+  end
+
+  describe "execute_kernel/5 with vector args" do
+    @vector_src """
+    __kernel void write_float4(__global float *out, float4 v, int3 i) {
+        out[0] = v.x; out[1] = v.y; out[2] = v.z; out[3] = v.w;
+        out[4] = i.x + i.y + i.z;
+    }
+    """
+
+    test "passes vector values by value" do
+      assert {:ok, [session | _]} = Session.create(@vector_src)
+      assert {:ok, out} = Session.create_buffer(session, :f32, 5)
+      args = [out, {:float4, {1.0, 2.0, 3.0, 4.0}}, {:int3, {1, 2, 3}}]
+      assert {:ok, ^out} = Session.execute_kernel(session, "write_float4", 1, args, returning: 0)
+      assert {:ok, array} = Session.read_buffer(session, out)
+      assert Array.to_list(array) == [1.0, 2.0, 3.0, 4.0, 6.0]
+    end
+
+    test "rejects components that do not match the vector width" do
+      assert {:ok, [session | _]} = Session.create(@vector_src)
+      assert {:ok, out} = Session.create_buffer(session, :f32, 5)
+      args = [out, {:float4, {1.0, 2.0}}, {:int3, {1, 2, 3}}]
+      assert {:error, _} = Session.execute_kernel(session, "write_float4", 1, args)
+    end
+
+    test "rejects components that do not fit the element type" do
+      assert {:ok, [session | _]} = Session.create(@vector_src)
+      assert {:ok, out} = Session.create_buffer(session, :f32, 5)
+      float4 = {:float4, {1.0, 2.0, 3.0, 4.0}}
+
+      for int3 <- [{:int3, {1, 2, 3_000_000_000}}, {:int3, {1, 2.5, 3}}] do
+        args = [out, float4, int3]
+        assert {:error, "DoesNotFit" <> _} = Session.execute_kernel(session, "write_float4", 1, args)
+      end
+    end
+  end
+
+  describe "f16 buffers" do
+    @half_src """
+    __kernel void double_half(__global half *data) {
+        size_t i = get_global_id(0);
+        vstore_half(vload_half(i, data) * 2.0f, i, data);
+    }
+    """
+
+    test "round-trip through a kernel as half floats" do
+      assert {:ok, [session | _]} = Session.create(@half_src)
+      assert {:ok, buffer} = Session.create_buffer(session, :f16, Array.new(:f16, [0.5, 1.5, 3.0]))
+      assert :ok = Session.execute_kernel(session, "double_half", 3, [buffer])
+      assert {:ok, array} = Session.read_buffer(session, buffer)
+      assert Array.type(array) == :f16
+      assert Array.to_list(array) == [1.0, 3.0, 6.0]
+    end
+  end
+
+  describe "complex buffers" do
+    @complex_src """
+    __kernel void scale_complex(__global float2 *data, float2 factor) {
+        size_t i = get_global_id(0);
+        float2 z = data[i];
+        data[i] = (float2)(z.x * factor.x - z.y * factor.y, z.x * factor.y + z.y * factor.x);
+    }
+    """
+
+    test "round-trip through a kernel as {re, im} tuples" do
+      assert {:ok, [session | _]} = Session.create(@complex_src)
+      data = Array.new(:c32, [{1.0, 0.0}, {0.0, 1.0}, {1.0, 1.0}])
+      assert {:ok, buffer} = Session.create_buffer(session, :c32, data)
+      assert :ok = Session.execute_kernel(session, "scale_complex", 3, [buffer, {0.0, 2.0}])
+      assert {:ok, array} = Session.read_buffer(session, buffer)
+      assert Array.type(array) == :c32
+      assert Array.to_list(array) == [{0.0, 2.0}, {-2.0, 0.0}, {-2.0, 2.0}]
+    end
+  end
 end
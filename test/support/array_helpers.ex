@@ -162,15 +162,21 @@ defmodule OpenCL.Test.ArrayHelpers do
   def to_array(number_type, data) when is_list(data), do: Array.new(number_type, data)
   def to_array(number_type, %Array{} = array), do: Array.type_cast(number_type, array)
 
-  @float_types [:f32, :f64]
+  @float_types [:f16, :f32, :f64]
   defguard is_float_t(x) when x in @float_types
 
+  @complex_types [:c32, :c64]
+  defguard is_complex_t(x) when x in @complex_types
+
   def convert(t, data) when is_list(data) do
     Enum.map(data, fn item -> convert(t, item) end)
   end
 
+  def convert(t, {re, im}) when is_complex_t(t), do: {re * 1.0, im * 1.0}
+  def convert(t, data) when is_complex_t(t) and is_number(data), do: {data * 1.0, 0.0}
   def convert(t, data) when is_float_t(t) and is_float(data), do: data
   def convert(t, data) when is_float_t(t) and is_integer(data), do: data * 1.0
+  def convert(t, {re, _im}), do: convert(t, re)
   def convert(t, data) when not is_float_t(t) and is_integer(data), do: data
   def convert(t, data) when not is_float_t(t) and is_float(data), do: Float.round(data)
 end